@@ -0,0 +1,127 @@
+// Apollo Allocation Policy
+// Data-driven target allocation: per-asset-class weights with floor/cap bounds and
+// the ability to disable a class entirely (e.g. an asset class with no reliable
+// price source), redistributing its weight proportionally across the remaining
+// enabled classes instead of a fixed risk-tolerance match arm.
+
+use std::collections::HashMap;
+
+/// One asset class's configured weight, enable/disable flag, and weight bounds.
+#[derive(Debug, Clone, Copy)]
+pub struct AssetClassPolicy {
+    pub weight: f64,
+    pub enabled: bool,
+    pub floor: f64,
+    pub cap: f64,
+}
+
+impl AssetClassPolicy {
+    pub fn new(weight: f64) -> Self {
+        Self { weight, enabled: true, floor: 0.0, cap: 1.0 }
+    }
+
+    pub fn with_bounds(mut self, floor: f64, cap: f64) -> Self {
+        self.floor = floor;
+        self.cap = cap;
+        self
+    }
+}
+
+/// Per-asset-class target weights, loaded per user/goal. Disabled classes are
+/// excluded entirely from `resolve_weights`, and floor/cap bounds are enforced
+/// regardless of risk tolerance (e.g. capping crypto at 45%).
+#[derive(Debug, Clone, Default)]
+pub struct AllocationPolicy {
+    classes: HashMap<String, AssetClassPolicy>,
+}
+
+impl AllocationPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_class(mut self, asset_class: impl Into<String>, policy: AssetClassPolicy) -> Self {
+        self.classes.insert(asset_class.into(), policy);
+        self
+    }
+
+    /// Excludes `asset_class` from the resolved allocation entirely -- its weight
+    /// is redistributed proportionally across the remaining enabled classes. Use
+    /// this when a class has no reliable price source, so the policy never
+    /// targets an allocation (and the generator never proposes a trade) in an
+    /// asset it can't price.
+    pub fn disable_class(mut self, asset_class: &str) -> Self {
+        if let Some(policy) = self.classes.get_mut(asset_class) {
+            policy.enabled = false;
+        }
+        self
+    }
+
+    /// Starting-point policies mirroring the previous hardcoded risk-tolerance
+    /// tuples, with crypto capped at 45% regardless of tolerance.
+    pub fn for_risk_tolerance(risk_tolerance: &str) -> Self {
+        let (stocks, crypto, nfts, cash) = match risk_tolerance {
+            "conservative" => (0.40, 0.10, 0.05, 0.45),
+            "aggressive" => (0.40, 0.45, 0.10, 0.05),
+            _ => (0.50, 0.30, 0.10, 0.10),
+        };
+
+        Self::new()
+            .with_class("stocks", AssetClassPolicy::new(stocks))
+            .with_class("crypto", AssetClassPolicy::new(crypto).with_bounds(0.0, 0.45))
+            .with_class("nfts", AssetClassPolicy::new(nfts))
+            .with_class("cash", AssetClassPolicy::new(cash))
+    }
+
+    /// Resolves final per-class weights: disabled classes are dropped, each
+    /// remaining weight is clamped to its floor/cap, and the result is
+    /// renormalized so enabled classes' weights sum to 1.0. Renormalizing after a
+    /// clamp can push a weight back outside its bounds (e.g. disabling `nfts`
+    /// leaves `crypto` renormalized past its 45% cap), so clamp and renormalize
+    /// repeatedly until a pass changes nothing.
+    pub fn resolve_weights(&self) -> HashMap<String, f64> {
+        let bounds: HashMap<&String, (f64, f64)> = self
+            .classes
+            .iter()
+            .filter(|(_, policy)| policy.enabled)
+            .map(|(class, policy)| (class, (policy.floor, policy.cap)))
+            .collect();
+
+        let mut weights: HashMap<String, f64> = self
+            .classes
+            .iter()
+            .filter(|(_, policy)| policy.enabled)
+            .map(|(class, policy)| (class.clone(), policy.weight))
+            .collect();
+
+        for _ in 0..Self::MAX_RESOLVE_ITERATIONS {
+            for (class, weight) in weights.iter_mut() {
+                let (floor, cap) = bounds[class];
+                *weight = weight.clamp(floor, cap);
+            }
+
+            let total: f64 = weights.values().sum();
+            if total <= f64::EPSILON {
+                break;
+            }
+            let mut stable = true;
+            for weight in weights.values_mut() {
+                let renormalized = *weight / total;
+                if (renormalized - *weight).abs() > Self::CONVERGENCE_TOLERANCE {
+                    stable = false;
+                }
+                *weight = renormalized;
+            }
+            if stable {
+                break;
+            }
+        }
+        weights
+    }
+
+    /// Upper bound on clamp/renormalize passes in `resolve_weights` -- bounded
+    /// weights converge in a handful of iterations, this just guards against an
+    /// unsatisfiable floor/cap configuration oscillating forever.
+    const MAX_RESOLVE_ITERATIONS: usize = 16;
+    const CONVERGENCE_TOLERANCE: f64 = 1e-9;
+}
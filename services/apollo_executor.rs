@@ -2,14 +2,21 @@
 // Main orchestration service that reads from Atlas and executes via Delt + Brokerages
 
 use crate::services::atlas_integration::{AtlasClient, ApolloStrategy, StrategyAction};
-use crate::services::delt_client::DeltClient;
+use crate::services::chain_valuator::ChainValuator;
+use crate::services::delt_client::{CryptoPosition, DeltClient, OrderStatus};
 use crate::services::brokerage_client::{UnifiedBrokerageClient, StockOrder};
+use crate::services::execution_store::{idempotency_key, ActionState, ExecutionStore};
+use crate::services::price_oracle::PriceOracle;
+use crate::services::rebalancer::{AllocationLeg, Rebalancer, RebalanceOutcome, RebalancePlan};
 use crate::services::strategy_generator::StrategyGenerator;
+use crate::services::triggers;
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::time::{sleep, Duration};
 use tracing::{info, warn, error};
+use uuid::Uuid;
 
 /// Apollo Executor - the brain of the AI agent
 pub struct ApolloExecutor {
@@ -17,6 +24,10 @@ pub struct ApolloExecutor {
     delt: Arc<DeltClient>,
     brokerage: Arc<UnifiedBrokerageClient>,
     strategy_gen: Arc<StrategyGenerator>,
+    store: Arc<ExecutionStore>,
+    chain_valuator: Arc<ChainValuator>,
+    price_oracle: Arc<PriceOracle>,
+    rebalancer: Arc<Rebalancer>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,12 +62,20 @@ impl ApolloExecutor {
         delt: Arc<DeltClient>,
         brokerage: Arc<UnifiedBrokerageClient>,
         strategy_gen: Arc<StrategyGenerator>,
+        store: Arc<ExecutionStore>,
+        chain_valuator: Arc<ChainValuator>,
+        price_oracle: Arc<PriceOracle>,
+        rebalancer: Arc<Rebalancer>,
     ) -> Self {
         Self {
             atlas,
             delt,
             brokerage,
             strategy_gen,
+            store,
+            chain_valuator,
+            price_oracle,
+            rebalancer,
         }
     }
 
@@ -99,7 +118,10 @@ impl ApolloExecutor {
         Ok(result)
     }
 
-    /// Execute a strategy
+    /// Execute a strategy. Each action is placed at most once regardless of restarts:
+    /// before resuming, every action's durable state is read from the `ExecutionStore`,
+    /// so actions already `Submitted`/`Confirmed` are skipped (or reconciled) instead
+    /// of re-submitted, and the final `ExecutionResult` is reconstructed from the store.
     async fn execute_strategy(&self, strategy: &ApolloStrategy) -> Result<ExecutionResult> {
         let start_time = std::time::Instant::now();
         let mut executed = Vec::new();
@@ -108,22 +130,80 @@ impl ApolloExecutor {
         // Mark as executing in Atlas
         self.atlas.mark_strategy_executing(&strategy.strategy_id).await?;
 
-        // Execute each action
+        // Resolve current prices once per run so every action's trigger (stop-loss,
+        // limit entry) can be checked against the same snapshot.
+        let mut current_prices = HashMap::new();
         for action in &strategy.actions {
-            match self.execute_action(action).await {
+            if !current_prices.contains_key(&action.symbol) {
+                if let Ok(price) = self.price_oracle.spot(&action.symbol, &action.asset_class).await {
+                    current_prices.insert(action.symbol.clone(), price);
+                }
+            }
+        }
+
+        for (index, action) in strategy.actions.iter().enumerate() {
+            match self.store.get(&strategy.strategy_id, index)? {
+                ActionState::Confirmed { result_id, price } => {
+                    executed.push(ExecutedAction {
+                        action: action.clone(),
+                        result_id,
+                        executed_at: chrono::Utc::now().to_rfc3339(),
+                        executed_price: price,
+                    });
+                    continue;
+                }
+                ActionState::Failed { error } => {
+                    failed.push(FailedAction { action: action.clone(), error, failed_at: chrono::Utc::now().to_rfc3339() });
+                    continue;
+                }
+                ActionState::Submitted { result_id, idempotency_key } => {
+                    // A previous run dispatched this action but the process didn't
+                    // observe the outcome -- reconcile by polling rather than
+                    // re-placing the order.
+                    match self.reconcile_submitted(&strategy.strategy_id, index, &result_id, &idempotency_key).await {
+                        Ok(executed_price) => {
+                            executed.push(ExecutedAction {
+                                action: action.clone(),
+                                result_id,
+                                executed_at: chrono::Utc::now().to_rfc3339(),
+                                executed_price,
+                            });
+                        }
+                        Err(e) => {
+                            warn!("Failed to reconcile submitted action: {:?} -> {}", action, e);
+                            failed.push(FailedAction { action: action.clone(), error: e.to_string(), failed_at: chrono::Utc::now().to_rfc3339() });
+                        }
+                    }
+                    continue;
+                }
+                ActionState::Pending => {}
+            }
+
+            if !triggers::is_ready(action, &current_prices) {
+                info!("Action {:?} is waiting on its trigger; leaving it pending", action);
+                continue;
+            }
+
+            match self.execute_action(&strategy.strategy_id, index, action).await {
                 Ok(result_id) => {
                     info!("Executed action: {:?} -> {}", action, result_id);
-                    
+
+                    // Best-effort: resolve the spot price at fill time for cost-basis
+                    // tracking. A missed lookup still leaves the fill `Confirmed`.
+                    let executed_price = self.price_oracle.spot(&action.symbol, &action.asset_class).await.ok();
+
+                    self.store.record_confirmed(&strategy.strategy_id, index, &result_id, executed_price)?;
                     executed.push(ExecutedAction {
                         action: action.clone(),
                         result_id,
                         executed_at: chrono::Utc::now().to_rfc3339(),
-                        executed_price: None, // TODO: Get actual price
+                        executed_price,
                     });
                 }
                 Err(e) => {
                     warn!("Failed to execute action: {:?} -> {}", action, e);
-                    
+
+                    self.store.record_failed(&strategy.strategy_id, index, &e.to_string())?;
                     failed.push(FailedAction {
                         action: action.clone(),
                         error: e.to_string(),
@@ -149,18 +229,59 @@ impl ApolloExecutor {
         })
     }
 
-    /// Execute a single action
-    async fn execute_action(&self, action: &StrategyAction) -> Result<String> {
+    /// Poll the downstream venue for an order that was `Submitted` on a prior run but
+    /// never confirmed locally, and move it to `Confirmed`/`Failed` accordingly.
+    ///
+    /// `result_id` still equalling `idempotency_key` means the process crashed between
+    /// the pre-dispatch placeholder write and the real id coming back -- the venue has
+    /// no order under the client idempotency key (it's not a lookup key downstream
+    /// supports), so polling it would just surface an opaque 404. Flag that window
+    /// explicitly instead of letting it masquerade as a normal poll failure.
+    async fn reconcile_submitted(
+        &self,
+        strategy_id: &str,
+        index: usize,
+        result_id: &str,
+        idempotency_key: &str,
+    ) -> Result<Option<f64>> {
+        if result_id == idempotency_key {
+            return Err(anyhow::anyhow!(
+                "action {strategy_id}:{index} crashed between dispatch and receiving its venue id; \
+                 its true state is unknown and requires manual reconciliation against the venue"
+            ));
+        }
+
+        let status = self.delt.get_order_status(result_id).await.context("failed to poll order status")?;
+
+        match status {
+            OrderStatus::Confirmed { price } => {
+                self.store.record_confirmed(strategy_id, index, result_id, Some(price))?;
+                Ok(Some(price))
+            }
+            OrderStatus::Failed { error } => {
+                self.store.record_failed(strategy_id, index, &error)?;
+                Err(anyhow::anyhow!(error))
+            }
+            OrderStatus::Pending => Err(anyhow::anyhow!("order {} is still pending", result_id)),
+        }
+    }
+
+    /// Execute a single action, deriving a deterministic idempotency key so a retried
+    /// submission of the same action is rejected server-side instead of double-trading,
+    /// and persisting the `Submitted` row the instant the request is dispatched.
+    async fn execute_action(&self, strategy_id: &str, index: usize, action: &StrategyAction) -> Result<String> {
+        let key = idempotency_key(strategy_id, index, &action.symbol, &action.side, action.amount);
+
         match action.asset_class.as_str() {
-            "crypto" => self.execute_crypto_action(action).await,
-            "stocks" => self.execute_stock_action(action).await,
-            "nft" => self.execute_nft_action(action).await,
+            "crypto" => self.execute_crypto_action(strategy_id, index, action, &key).await,
+            "stocks" => self.execute_stock_action(strategy_id, index, action, &key).await,
+            "nft" => self.execute_nft_action(strategy_id, index, action, &key).await,
             _ => Err(anyhow::anyhow!("Unknown asset class: {}", action.asset_class)),
         }
     }
 
     /// Execute crypto action via Delt
-    async fn execute_crypto_action(&self, action: &StrategyAction) -> Result<String> {
+    async fn execute_crypto_action(&self, strategy_id: &str, index: usize, action: &StrategyAction, idempotency_key: &str) -> Result<String> {
         info!("Executing crypto action: {} {} {}", action.side, action.amount, action.symbol);
 
         let order = serde_json::json!({
@@ -169,16 +290,23 @@ impl ApolloExecutor {
             "side": action.side,
             "amount_usd": action.amount,
             "order_type": "market",
+            "idempotency_key": idempotency_key,
         });
 
-        let tx_hash = self.delt.place_order(&order).await
+        // Persist a `Submitted` row under the idempotency key before dispatch, so a
+        // crash while the request is in flight still leaves a row behind to
+        // reconcile on resume instead of silently re-dispatching. Once the real tx
+        // hash comes back it replaces the provisional one.
+        self.store.record_submitted(strategy_id, index, idempotency_key, idempotency_key)?;
+        let tx_hash = self.delt.place_order(&order, idempotency_key).await
             .context("Failed to place crypto order")?;
+        self.store.record_submitted(strategy_id, index, &tx_hash, idempotency_key)?;
 
         Ok(tx_hash)
     }
 
     /// Execute stock action via brokerage
-    async fn execute_stock_action(&self, action: &StrategyAction) -> Result<String> {
+    async fn execute_stock_action(&self, strategy_id: &str, index: usize, action: &StrategyAction, idempotency_key: &str) -> Result<String> {
         info!("Executing stock action: {} {} {}", action.side, action.amount, action.symbol);
 
         // Calculate quantity (amount in USD / current price)
@@ -195,34 +323,37 @@ impl ApolloExecutor {
             time_in_force: "day".to_string(),
         };
 
+        // Persist a `Submitted` row under the idempotency key before dispatch, so a
+        // crash while the request is in flight still leaves a row behind to
+        // reconcile on resume instead of silently re-dispatching. Once the real
+        // order id comes back it replaces the provisional one.
+        self.store.record_submitted(strategy_id, index, idempotency_key, idempotency_key)?;
         let order_id = self.brokerage.place_order(order).await
             .context("Failed to place stock order")?;
+        self.store.record_submitted(strategy_id, index, &order_id, idempotency_key)?;
 
         Ok(order_id)
     }
 
     /// Execute NFT action via Delt
-    async fn execute_nft_action(&self, action: &StrategyAction) -> Result<String> {
+    async fn execute_nft_action(&self, strategy_id: &str, index: usize, action: &StrategyAction, idempotency_key: &str) -> Result<String> {
         info!("Executing NFT action: {}", action.action_type);
 
-        match action.action_type.as_str() {
-            "breed" => {
-                // Call Delt NFT breeding endpoint
-                let result = self.delt.breed_nft(&action.symbol).await?;
-                Ok(result)
-            }
-            "buy" => {
-                // Buy NFT from marketplace
-                let result = self.delt.buy_nft(&action.symbol, action.amount).await?;
-                Ok(result)
-            }
-            "lock_collateral" => {
-                // Lock NFT as DELT collateral
-                let result = self.delt.lock_nft_collateral(&action.symbol).await?;
-                Ok(result)
-            }
-            _ => Err(anyhow::anyhow!("Unknown NFT action: {}", action.action_type)),
-        }
+        // Persist a `Submitted` row under the idempotency key before dispatch, so a
+        // crash while the request is in flight still leaves a row behind to
+        // reconcile on resume instead of silently re-dispatching. Once the real
+        // result id comes back it replaces the provisional one.
+        self.store.record_submitted(strategy_id, index, idempotency_key, idempotency_key)?;
+
+        let result = match action.action_type.as_str() {
+            "breed" => self.delt.breed_nft(&action.symbol, idempotency_key).await,
+            "buy" => self.delt.buy_nft(&action.symbol, action.amount, idempotency_key).await,
+            "lock_collateral" => self.delt.lock_nft_collateral(&action.symbol, idempotency_key).await,
+            _ => return Err(anyhow::anyhow!("Unknown NFT action: {}", action.action_type)),
+        }?;
+
+        self.store.record_submitted(strategy_id, index, &result, idempotency_key)?;
+        Ok(result)
     }
 
     /// Report execution results back to Atlas
@@ -255,61 +386,181 @@ impl ApolloExecutor {
         Ok(())
     }
 
+    /// Values the user's crypto holdings from the chain itself (trust-minimized)
+    /// rather than trusting Delt's self-reported figure, marks every position to
+    /// market via the `PriceOracle`, and flags any discrepancy between the two.
     async fn calculate_new_portfolio_value(&self, result: &ExecutionResult) -> Result<f64> {
-        // TODO: Query actual portfolio value from Delt + brokerages
-        // For now, return placeholder
-        Ok(150000.0)
+        let portfolio = self.delt.get_portfolio(&result.user_id).await.ok();
+        let delt_value = match &portfolio {
+            Some(p) => self.mark_to_market_value(p).await.unwrap_or(None),
+            None => None,
+        };
+
+        // TODO: derive real receive addresses from the user's wallet/xpub once a
+        // wallet registry exists; this watches a single deterministic address.
+        let user_id = result.user_id.clone();
+        let btc_price_usd = self.price_oracle.spot("BTC", "crypto").await.context("failed to resolve BTC spot price")?;
+
+        let valuation = self
+            .chain_valuator
+            .value_holdings("BTC", move |index| format!("{}-watch-{}", user_id, index), btc_price_usd, delt_value)
+            .await
+            .context("failed to independently value on-chain holdings")?;
+
+        if let Some(discrepancy) = valuation.discrepancy_usd {
+            if discrepancy > valuation.total_value_usd.max(1.0) * 0.05 {
+                warn!(
+                    "chain valuation of {} diverges from Delt by ${:.2} (chain=${:.2}, delt={:?})",
+                    valuation.symbol, discrepancy, valuation.total_value_usd, valuation.delt_reported_value_usd
+                );
+            }
+        }
+
+        let stock_value = self.brokerage.get_total_value().await.unwrap_or(0.0);
+        Ok(valuation.total_value_usd + stock_value)
     }
 
-    /// Monitor and rebalance (run periodically)
+    /// Recomputes market value and unrealized PnL per `CryptoPosition` against live
+    /// spot prices, rather than trusting Delt's self-reported `current_price`.
+    async fn mark_to_market_value(&self, portfolio: &serde_json::Value) -> Result<Option<f64>> {
+        let positions: Vec<CryptoPosition> = match portfolio.get("positions") {
+            Some(p) => serde_json::from_value(p.clone()).context("failed to parse portfolio positions")?,
+            None => return Ok(None),
+        };
+
+        let mut total_value = 0.0;
+        for position in &positions {
+            let spot_price = self.price_oracle.spot(&position.symbol, "crypto").await.unwrap_or(position.current_price);
+            let market_value = spot_price * position.quantity;
+            let unrealized_pnl = (spot_price - position.avg_entry_price) * position.quantity;
+            info!(
+                "position {}: market_value=${:.2} unrealized_pnl=${:.2}",
+                position.symbol, market_value, unrealized_pnl
+            );
+            total_value += market_value;
+        }
+
+        Ok(Some(total_value))
+    }
+
+    /// Monitor and rebalance (run periodically), using `Rebalancer` to compute
+    /// concrete per-leg dollar deltas and an ordered action list instead of a single
+    /// drift scalar compared against a fixed 5% threshold.
     pub async fn monitor_and_rebalance(&self, user_id: &str) -> Result<()> {
         info!("Monitoring portfolio for user: {}", user_id);
 
-        // 1. Get current allocation
         let crypto_value = self.delt.get_portfolio_value(user_id).await?;
         let stock_value = self.brokerage.get_total_value().await?;
-        let total = crypto_value + stock_value;
-
-        let current_allocation = serde_json::json!({
-            "crypto": crypto_value / total,
-            "stocks": stock_value / total,
-        });
 
-        // 2. Get target allocation from active strategy
         let profile = self.atlas.get_complete_profile(user_id).await?;
-        
-        if let Some(active_strategy) = profile.active_strategies.first() {
-            let target_allocation = &active_strategy.target_allocation;
-
-            // 3. Calculate drift
-            let drift = self.calculate_drift(&current_allocation, target_allocation);
-
-            // 4. If drift > 5%, trigger rebalance
-            if drift > 0.05 {
-                info!("Portfolio drift detected: {:.2}%. Rebalancing...", drift * 100.0);
-                
-                // Generate rebalance strategy
-                let rebalance_strategy = self.strategy_gen.generate_strategy(user_id).await?;
-                
-                // Execute if auto_execute enabled
-                if profile.goals.iter().any(|g| g.auto_execute) {
-                    self.execute_strategy(&rebalance_strategy).await?;
-                } else {
-                    // Just notify user
-                    info!("Rebalance needed but auto_execute disabled. Notifying user.");
-                }
+
+        let Some(active_strategy) = profile.active_strategies.first() else {
+            return Ok(());
+        };
+
+        let target_crypto = active_strategy.target_allocation.get("crypto").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let target_stocks = active_strategy.target_allocation.get("stocks").and_then(|v| v.as_f64()).unwrap_or(0.0);
+
+        let legs = vec![
+            AllocationLeg { asset_class: "crypto".to_string(), symbol: "BTC".to_string(), current_value_usd: crypto_value, target_value_usd: target_crypto },
+            AllocationLeg { asset_class: "stocks".to_string(), symbol: "SPY".to_string(), current_value_usd: stock_value, target_value_usd: target_stocks },
+        ];
+
+        let plan = self.rebalancer.plan(&legs);
+        if plan.actions.is_empty() {
+            return Ok(());
+        }
+
+        info!("Portfolio drift detected across {} leg(s). Rebalancing...", plan.actions.len());
+
+        if !profile.goals.iter().any(|g| g.auto_execute) {
+            info!("Rebalance needed but auto_execute disabled. Notifying user.");
+            return Ok(());
+        }
+
+        match self.execute_rebalance_plan(plan).await? {
+            RebalanceOutcome::Applied => info!("Rebalance fully applied for user {}", user_id),
+            RebalanceOutcome::PartiallyApplied { failed_leg_index, residual_drift } => {
+                warn!(
+                    "Rebalance for user {} partially applied (failed at leg {}); residual drift: {:?}",
+                    user_id, failed_leg_index, residual_drift
+                );
             }
         }
 
         Ok(())
     }
 
-    fn calculate_drift(&self, current: &serde_json::Value, target: &serde_json::Value) -> f64 {
-        // Calculate total drift across all allocations
-        let current_crypto = current.get("crypto").and_then(|v| v.as_f64()).unwrap_or(0.0);
-        let target_crypto = target.get("crypto").and_then(|v| v.as_f64()).unwrap_or(0.0);
-        
-        (current_crypto - target_crypto).abs()
+    /// Executes a `RebalancePlan` leg-by-leg (sells before buys, per
+    /// `Rebalancer::plan`). If a leg fails, compensates by buying back any
+    /// already-filled sells -- the proceeds they raised are no longer needed once a
+    /// dependent buy never lands -- rather than leaving the portfolio skewed further
+    /// than before the rebalance started.
+    async fn execute_rebalance_plan(&self, plan: RebalancePlan) -> Result<RebalanceOutcome> {
+        let strategy_id = format!("rebalance-{}", Uuid::new_v4());
+        let mut filled = Vec::new();
+
+        for (index, action) in plan.actions.iter().enumerate() {
+            match self.execute_action(&strategy_id, index, action).await {
+                Ok(result_id) => {
+                    self.store.record_confirmed(&strategy_id, index, &result_id, None)?;
+                    filled.push(action);
+                }
+                Err(e) => {
+                    warn!("Rebalance leg failed: {:?} -> {}", action, e);
+                    self.store.record_failed(&strategy_id, index, &e.to_string())?;
+
+                    let mut rolled_back_symbols = HashSet::new();
+                    for (rollback_offset, filled_action) in filled.iter().rev().enumerate() {
+                        if filled_action.side == "sell" {
+                            let reverse = StrategyAction {
+                                action_type: "buy".to_string(),
+                                asset_class: filled_action.asset_class.clone(),
+                                symbol: filled_action.symbol.clone(),
+                                side: "buy".to_string(),
+                                amount: filled_action.amount,
+                                reason: "Compensating rollback of a partially applied rebalance".to_string(),
+                                priority: 0,
+                                trigger: None,
+                            };
+                            let rollback_slot = plan.actions.len() + rollback_offset;
+                            match self.execute_action(&strategy_id, rollback_slot, &reverse).await {
+                                Ok(_) => {
+                                    rolled_back_symbols.insert(filled_action.symbol.clone());
+                                }
+                                Err(rollback_err) => {
+                                    // The original sell went through but the buy-back
+                                    // didn't -- the position is now further from
+                                    // target than before the rebalance, not restored,
+                                    // so its symbol must NOT be reported as rolled
+                                    // back below.
+                                    error!("Failed to roll back rebalance leg {:?}: {}", filled_action, rollback_err);
+                                }
+                            }
+                        }
+                    }
+
+                    // Report drift for legs that never got a chance to run (index..)
+                    // as well as legs that did run but were just rolled back -- the
+                    // rollback put those symbols back at their pre-rebalance drift,
+                    // not at zero, so omitting them would understate how far the
+                    // portfolio still is from target.
+                    let residual_drift = plan
+                        .residual_drift
+                        .iter()
+                        .filter(|(symbol, _)| {
+                            plan.actions[index..].iter().any(|a| &a.symbol == *symbol)
+                                || rolled_back_symbols.contains(*symbol)
+                        })
+                        .map(|(symbol, delta)| (symbol.clone(), *delta))
+                        .collect();
+
+                    return Ok(RebalanceOutcome::PartiallyApplied { failed_leg_index: index, residual_drift });
+                }
+            }
+        }
+
+        Ok(RebalanceOutcome::Applied)
     }
 }
 
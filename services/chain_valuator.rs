@@ -0,0 +1,127 @@
+// Apollo Chain Valuator
+// Independently values on-chain crypto holdings via an Esplora-style blockchain
+// indexer instead of trusting whatever a single venue (Delt) self-reports.
+
+use anyhow::Result;
+use reqwest::Client;
+use serde::Deserialize;
+
+/// Number of consecutive unused addresses to scan past before stopping discovery --
+/// the "stop-gap" convention BIP-44 wallet scanners use to bound an otherwise
+/// infinite derivation sequence.
+const DEFAULT_STOP_GAP: usize = 20;
+
+pub struct ChainValuator {
+    esplora_url: String,
+    client: Client,
+    stop_gap: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct AddressBalance {
+    pub address: String,
+    pub balance_sats: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainValuation {
+    pub symbol: String,
+    pub total_balance: f64,
+    pub total_value_usd: f64,
+    pub addresses_scanned: usize,
+    pub delt_reported_value_usd: Option<f64>,
+    pub discrepancy_usd: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct EsploraAddressStats {
+    chain_stats: EsploraChainStats,
+    mempool_stats: EsploraChainStats,
+}
+
+#[derive(Deserialize)]
+struct EsploraChainStats {
+    funded_txo_sum: u64,
+    spent_txo_sum: u64,
+}
+
+impl ChainValuator {
+    pub fn new(esplora_url: impl Into<String>) -> Self {
+        Self { esplora_url: esplora_url.into(), client: Client::new(), stop_gap: DEFAULT_STOP_GAP }
+    }
+
+    pub fn with_stop_gap(mut self, stop_gap: usize) -> Self {
+        self.stop_gap = stop_gap;
+        self
+    }
+
+    /// Query the balance of a single address from the indexer, summing confirmed
+    /// and unconfirmed UTXOs.
+    async fn address_balance(&self, address: &str) -> Result<u64> {
+        let url = format!("{}/address/{}", self.esplora_url, address);
+        let stats: EsploraAddressStats = self.client.get(&url).send().await?.json().await?;
+
+        let confirmed = stats.chain_stats.funded_txo_sum.saturating_sub(stats.chain_stats.spent_txo_sum);
+        let unconfirmed = stats.mempool_stats.funded_txo_sum.saturating_sub(stats.mempool_stats.spent_txo_sum);
+        Ok(confirmed + unconfirmed)
+    }
+
+    /// Derive addresses sequentially via `derive_address(index)` and query their
+    /// balances, stopping after `stop_gap` consecutive unused addresses.
+    pub async fn scan_addresses(&self, derive_address: impl Fn(usize) -> String) -> Result<Vec<AddressBalance>> {
+        let mut found = Vec::new();
+        let mut consecutive_unused = 0;
+        let mut index = 0;
+
+        while consecutive_unused < self.stop_gap {
+            let address = derive_address(index);
+            // A single address lookup failing (bad/placeholder address, indexer
+            // hiccup) shouldn't abort the whole scan -- degrade the same way
+            // `price_history.rs::daily_closes` does for a missing data point and
+            // treat it as unused toward the stop-gap instead of propagating.
+            let balance_sats = match self.address_balance(&address).await {
+                Ok(balance_sats) => balance_sats,
+                Err(e) => {
+                    tracing::warn!(error = %e, address = %address, "failed to query address balance, treating as unused");
+                    0
+                }
+            };
+
+            if balance_sats == 0 {
+                consecutive_unused += 1;
+            } else {
+                consecutive_unused = 0;
+                found.push(AddressBalance { address, balance_sats });
+            }
+
+            index += 1;
+        }
+
+        Ok(found)
+    }
+
+    /// Scan for on-chain balance, price it, and flag any discrepancy against what
+    /// Delt self-reports for the same symbol.
+    pub async fn value_holdings(
+        &self,
+        symbol: &str,
+        derive_address: impl Fn(usize) -> String,
+        price_per_coin: f64,
+        delt_reported_value_usd: Option<f64>,
+    ) -> Result<ChainValuation> {
+        let balances = self.scan_addresses(derive_address).await?;
+        let total_balance_sats: u64 = balances.iter().map(|b| b.balance_sats).sum();
+        let total_balance = total_balance_sats as f64 / 1e8;
+        let total_value_usd = total_balance * price_per_coin;
+        let discrepancy_usd = delt_reported_value_usd.map(|v| (total_value_usd - v).abs());
+
+        Ok(ChainValuation {
+            symbol: symbol.to_string(),
+            total_balance,
+            total_value_usd,
+            addresses_scanned: balances.len(),
+            delt_reported_value_usd,
+            discrepancy_usd,
+        })
+    }
+}
@@ -1,15 +1,32 @@
 // Apollo Delt Client
 // Integrates with Delt for crypto trading, NFT operations, etc.
 
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, Context};
+use std::sync::Arc;
+
+use crate::services::transport::{Transport, TransportBuilder, TransportLayer, TransportRequest};
 
 /// Delt client for Apollo to execute crypto trades
 pub struct DeltClient {
     base_url: String,
-    api_key: String,
-    client: Client,
+    transport: Arc<dyn Transport>,
+}
+
+pub struct DeltClientBuilder {
+    base_url: String,
+    transport: TransportBuilder,
+}
+
+impl DeltClientBuilder {
+    pub fn with(mut self, layer: impl TransportLayer + 'static) -> Self {
+        self.transport = self.transport.with(layer);
+        self
+    }
+
+    pub fn build(self) -> DeltClient {
+        DeltClient { base_url: self.base_url, transport: self.transport.build() }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +38,14 @@ pub struct CryptoOrder {
     pub order_type: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    Pending,
+    Confirmed { price: f64 },
+    Failed { error: String },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CryptoPosition {
     pub symbol: String,
@@ -33,31 +58,26 @@ pub struct CryptoPosition {
 
 impl DeltClient {
     pub fn new(base_url: String, api_key: String) -> Self {
-        Self {
-            base_url,
-            api_key,
-            client: Client::new(),
-        }
+        Self::builder(base_url, api_key).build()
+    }
+
+    /// Compose a `DeltClient` with a custom transport stack, e.g.
+    /// `DeltClient::builder(url, key).with(RetryMiddleware::default()).with(RateLimitMiddleware::per_minute(60)).build()`.
+    pub fn builder(base_url: String, api_key: String) -> DeltClientBuilder {
+        DeltClientBuilder { base_url, transport: TransportBuilder::new(api_key) }
     }
 
     /// Get user's crypto portfolio
     pub async fn get_portfolio(&self, user_id: &str) -> Result<serde_json::Value> {
         let url = format!("{}/api/v1/portfolio/{}", self.base_url, user_id);
-
-        let response = self.client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .send()
-            .await?;
-
-        let portfolio = response.json().await?;
+        let portfolio = self.transport.send(TransportRequest::get(url)).await?;
         Ok(portfolio)
     }
 
     /// Get total portfolio value in USD
     pub async fn get_portfolio_value(&self, user_id: &str) -> Result<f64> {
         let portfolio = self.get_portfolio(user_id).await?;
-        
+
         // Parse positions and sum values
         let positions = portfolio.get("positions")
             .and_then(|p| p.as_array())
@@ -70,94 +90,84 @@ impl DeltClient {
         Ok(total)
     }
 
-    /// Place crypto order
-    pub async fn place_order(&self, order: &serde_json::Value) -> Result<String> {
+    /// Place crypto order. `idempotency_key` is threaded onto the `TransportRequest`
+    /// (not just the JSON body) so `RetryMiddleware` retries this non-`GET` request
+    /// on transient failure and echoes the same client reference on every attempt.
+    pub async fn place_order(&self, order: &serde_json::Value, idempotency_key: &str) -> Result<String> {
         let url = format!("{}/api/v1/orders", self.base_url);
-
-        let response = self.client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(order)
-            .send()
-            .await?;
+        let request = TransportRequest::post_json(url, order)?.with_idempotency_key(idempotency_key);
 
         #[derive(Deserialize)]
         struct OrderResponse {
             tx_hash: String,
         }
 
-        let result: OrderResponse = response.json().await?;
+        let value = self.transport.send(request).await?;
+        let result: OrderResponse = serde_json::from_value(value)?;
         Ok(result.tx_hash)
     }
 
     /// Breed NFT
-    pub async fn breed_nft(&self, parent_ids: &str) -> Result<String> {
+    pub async fn breed_nft(&self, parent_ids: &str, idempotency_key: &str) -> Result<String> {
         let url = format!("{}/api/v1/nft/breed", self.base_url);
-
-        let response = self.client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&serde_json::json!({
-                "parent_ids": parent_ids,
-                "payment_type": "WTF"
-            }))
-            .send()
-            .await?;
+        let request = TransportRequest::post_json(url, &serde_json::json!({
+            "parent_ids": parent_ids,
+            "payment_type": "WTF"
+        }))?.with_idempotency_key(idempotency_key);
 
         #[derive(Deserialize)]
         struct BreedResponse {
             token_id: String,
         }
 
-        let result: BreedResponse = response.json().await?;
+        let value = self.transport.send(request).await?;
+        let result: BreedResponse = serde_json::from_value(value)?;
         Ok(result.token_id)
     }
 
     /// Buy NFT from marketplace
-    pub async fn buy_nft(&self, token_id: &str, max_price: f64) -> Result<String> {
+    pub async fn buy_nft(&self, token_id: &str, max_price: f64, idempotency_key: &str) -> Result<String> {
         let url = format!("{}/api/v1/nft/buy", self.base_url);
-
-        let response = self.client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&serde_json::json!({
-                "token_id": token_id,
-                "max_price": max_price
-            }))
-            .send()
-            .await?;
+        let request = TransportRequest::post_json(url, &serde_json::json!({
+            "token_id": token_id,
+            "max_price": max_price
+        }))?.with_idempotency_key(idempotency_key);
 
         #[derive(Deserialize)]
         struct BuyResponse {
             tx_hash: String,
         }
 
-        let result: BuyResponse = response.json().await?;
+        let value = self.transport.send(request).await?;
+        let result: BuyResponse = serde_json::from_value(value)?;
         Ok(result.tx_hash)
     }
 
+    /// Look up the current status of a previously placed order/tx, used to reconcile
+    /// actions that were `Submitted` but whose outcome wasn't observed (e.g. the
+    /// process crashed before the response came back).
+    pub async fn get_order_status(&self, tx_hash: &str) -> Result<OrderStatus> {
+        let url = format!("{}/api/v1/orders/{}", self.base_url, tx_hash);
+        let value = self.transport.send(TransportRequest::get(url)).await?;
+        let status: OrderStatus = serde_json::from_value(value)?;
+        Ok(status)
+    }
+
     /// Lock NFT as DELT collateral
-    pub async fn lock_nft_collateral(&self, token_id: &str) -> Result<String> {
+    pub async fn lock_nft_collateral(&self, token_id: &str, idempotency_key: &str) -> Result<String> {
         let url = format!("{}/api/v1/stablecoin/lock-collateral", self.base_url);
-
-        let response = self.client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&serde_json::json!({
-                "nft_ids": [token_id],
-                "wtf_amount": 0
-            }))
-            .send()
-            .await?;
+        let request = TransportRequest::post_json(url, &serde_json::json!({
+            "nft_ids": [token_id],
+            "wtf_amount": 0
+        }))?.with_idempotency_key(idempotency_key);
 
         #[derive(Deserialize)]
         struct CollateralResponse {
             position_id: String,
         }
 
-        let result: CollateralResponse = response.json().await?;
+        let value = self.transport.send(request).await?;
+        let result: CollateralResponse = serde_json::from_value(value)?;
         Ok(result.position_id)
     }
 }
-
-
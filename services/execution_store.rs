@@ -0,0 +1,97 @@
+// Apollo Execution Store
+// Durable per-action state so `ApolloExecutor::execute_strategy` is idempotent across
+// restarts -- a crash (or a `report_results` failure) after some orders are placed must
+// not re-submit them on the next run.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// State of a single `StrategyAction` within a strategy, keyed by `(strategy_id, action_index)`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ActionState {
+    Pending,
+    Submitted { result_id: String, idempotency_key: String },
+    Confirmed { result_id: String, price: Option<f64> },
+    Failed { error: String },
+}
+
+/// Sled-backed store mapping `(strategy_id, action_index)` to the action's durable
+/// execution state.
+pub struct ExecutionStore {
+    db: sled::Db,
+}
+
+impl ExecutionStore {
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let db = sled::open(path).context("failed to open execution store")?;
+        Ok(Self { db })
+    }
+
+    fn key(strategy_id: &str, action_index: usize) -> String {
+        format!("{strategy_id}:{action_index}")
+    }
+
+    pub fn get(&self, strategy_id: &str, action_index: usize) -> Result<ActionState> {
+        let key = Self::key(strategy_id, action_index);
+        match self.db.get(key.as_bytes())? {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(ActionState::Pending),
+        }
+    }
+
+    fn put(&self, strategy_id: &str, action_index: usize, state: &ActionState) -> Result<()> {
+        let key = Self::key(strategy_id, action_index);
+        let bytes = serde_json::to_vec(state)?;
+        self.db.insert(key.as_bytes(), bytes)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Record that a submission was dispatched, the instant it goes out -- this must
+    /// happen before the downstream client call returns, so a crash mid-request still
+    /// leaves a `Submitted` row behind to reconcile on resume.
+    pub fn record_submitted(
+        &self,
+        strategy_id: &str,
+        action_index: usize,
+        result_id: &str,
+        idempotency_key: &str,
+    ) -> Result<()> {
+        self.put(
+            strategy_id,
+            action_index,
+            &ActionState::Submitted {
+                result_id: result_id.to_string(),
+                idempotency_key: idempotency_key.to_string(),
+            },
+        )
+    }
+
+    pub fn record_confirmed(
+        &self,
+        strategy_id: &str,
+        action_index: usize,
+        result_id: &str,
+        price: Option<f64>,
+    ) -> Result<()> {
+        self.put(strategy_id, action_index, &ActionState::Confirmed { result_id: result_id.to_string(), price })
+    }
+
+    pub fn record_failed(&self, strategy_id: &str, action_index: usize, error: &str) -> Result<()> {
+        self.put(strategy_id, action_index, &ActionState::Failed { error: error.to_string() })
+    }
+}
+
+/// Deterministic client-side idempotency key so a retried/duplicated submission of the
+/// same `(strategy_id, action_index, symbol, side, amount)` is rejected server-side
+/// instead of double-trading.
+pub fn idempotency_key(strategy_id: &str, action_index: usize, symbol: &str, side: &str, amount: f64) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(strategy_id.as_bytes());
+    hasher.update(action_index.to_le_bytes());
+    hasher.update(symbol.as_bytes());
+    hasher.update(side.as_bytes());
+    hasher.update(amount.to_bits().to_le_bytes());
+    format!("{:x}", hasher.finalize())
+}
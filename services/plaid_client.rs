@@ -5,6 +5,14 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, Context};
 use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::services::response_cache::ResponseCache;
+use crate::services::token_vault::{TokenHandle, TokenVault};
+
+/// How long a cached response stays fresh before a `_cached` call re-hits Plaid.
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(300);
 
 /// Plaid Banking Client
 pub struct PlaidClient {
@@ -12,6 +20,9 @@ pub struct PlaidClient {
     secret: String,
     environment: PlaidEnvironment,
     client: Client,
+    vault: Arc<TokenVault>,
+    cache: ResponseCache,
+    cache_ttl: Duration,
 }
 
 #[derive(Debug, Clone)]
@@ -80,15 +91,31 @@ pub struct Investment {
 }
 
 impl PlaidClient {
-    pub fn new(client_id: String, secret: String, environment: PlaidEnvironment) -> Self {
+    pub fn new(client_id: String, secret: String, environment: PlaidEnvironment, vault: Arc<TokenVault>) -> Self {
+        // A pooled client shared across every request/task instead of one built
+        // per call, so TCP/TLS connections to Plaid are reused.
+        let client = Client::builder()
+            .pool_max_idle_per_host(16)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .build()
+            .expect("failed to build Plaid HTTP client");
+
         Self {
             client_id,
             secret,
             environment,
-            client: Client::new(),
+            client,
+            vault,
+            cache: ResponseCache::new(),
+            cache_ttl: DEFAULT_CACHE_TTL,
         }
     }
 
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
     /// Create a Link token for user to connect their bank
     pub async fn create_link_token(&self, user_id: &str) -> Result<LinkToken> {
         let url = format!("{}/link/token/create", self.environment.base_url());
@@ -132,8 +159,10 @@ impl PlaidClient {
         Ok(link_token)
     }
 
-    /// Exchange public token for access token
-    pub async fn exchange_public_token(&self, public_token: &str) -> Result<String> {
+    /// Exchange public token for an access token, which is immediately sealed in
+    /// the token vault -- callers only ever see the resulting handle, never the
+    /// plaintext Plaid access token.
+    pub async fn exchange_public_token(&self, public_token: &str) -> Result<TokenHandle> {
         let url = format!("{}/item/public_token/exchange", self.environment.base_url());
 
         #[derive(Serialize)]
@@ -146,6 +175,7 @@ impl PlaidClient {
         #[derive(Deserialize)]
         struct Response {
             access_token: String,
+            item_id: String,
         }
 
         let request = Request {
@@ -161,11 +191,11 @@ impl PlaidClient {
             .await?;
 
         let result: Response = response.json().await?;
-        Ok(result.access_token)
+        self.vault.store_token(&result.item_id, &result.access_token)
     }
 
     /// Get account balances
-    pub async fn get_balances(&self, access_token: &str) -> Result<Vec<Account>> {
+    pub async fn get_balances(&self, handle: &TokenHandle) -> Result<Vec<Account>> {
         let url = format!("{}/accounts/balance/get", self.environment.base_url());
 
         #[derive(Serialize)]
@@ -180,11 +210,11 @@ impl PlaidClient {
             accounts: Vec<Account>,
         }
 
-        let request = Request {
+        let request = self.vault.with_token(handle, |access_token| Request {
             client_id: self.client_id.clone(),
             secret: self.secret.clone(),
             access_token: access_token.to_string(),
-        };
+        })?;
 
         let response = self.client
             .post(&url)
@@ -199,7 +229,7 @@ impl PlaidClient {
     /// Get transactions
     pub async fn get_transactions(
         &self,
-        access_token: &str,
+        handle: &TokenHandle,
         start_date: &str,
         end_date: &str,
     ) -> Result<Vec<Transaction>> {
@@ -219,13 +249,13 @@ impl PlaidClient {
             transactions: Vec<Transaction>,
         }
 
-        let request = Request {
+        let request = self.vault.with_token(handle, |access_token| Request {
             client_id: self.client_id.clone(),
             secret: self.secret.clone(),
             access_token: access_token.to_string(),
             start_date: start_date.to_string(),
             end_date: end_date.to_string(),
-        };
+        })?;
 
         let response = self.client
             .post(&url)
@@ -238,7 +268,7 @@ impl PlaidClient {
     }
 
     /// Get investment holdings
-    pub async fn get_investments(&self, access_token: &str) -> Result<Vec<Investment>> {
+    pub async fn get_investments(&self, handle: &TokenHandle) -> Result<Vec<Investment>> {
         let url = format!("{}/investments/holdings/get", self.environment.base_url());
 
         #[derive(Serialize)]
@@ -253,11 +283,11 @@ impl PlaidClient {
             holdings: Vec<Investment>,
         }
 
-        let request = Request {
+        let request = self.vault.with_token(handle, |access_token| Request {
             client_id: self.client_id.clone(),
             secret: self.secret.clone(),
             access_token: access_token.to_string(),
-        };
+        })?;
 
         let response = self.client
             .post(&url)
@@ -269,10 +299,55 @@ impl PlaidClient {
         Ok(result.holdings)
     }
 
-    /// Calculate net worth from Plaid data
-    pub async fn calculate_net_worth(&self, access_token: &str) -> Result<f64> {
-        let accounts = self.get_balances(access_token).await?;
-        let investments = self.get_investments(access_token).await?;
+    /// Get account balances, serving a cached response (if fresh) instead of
+    /// hitting Plaid. Pass `force_refresh = true` to bypass the cache and
+    /// overwrite the cached entry with a fresh response.
+    pub async fn get_balances_cached(&self, handle: &TokenHandle, force_refresh: bool) -> Result<Vec<Account>> {
+        let key = self.cache_key(handle, "get_balances", "");
+        if !force_refresh {
+            if let Some(cached) = self.cache.get(&key, self.cache_ttl) {
+                return Ok(cached);
+            }
+        }
+
+        let accounts = self.get_balances(handle).await?;
+        self.cache.insert(key, &accounts);
+        Ok(accounts)
+    }
+
+    /// Get investment holdings, serving a cached response (if fresh) instead of
+    /// hitting Plaid. Pass `force_refresh = true` to bypass the cache and
+    /// overwrite the cached entry with a fresh response.
+    pub async fn get_investments_cached(&self, handle: &TokenHandle, force_refresh: bool) -> Result<Vec<Investment>> {
+        let key = self.cache_key(handle, "get_investments", "");
+        if !force_refresh {
+            if let Some(cached) = self.cache.get(&key, self.cache_ttl) {
+                return Ok(cached);
+            }
+        }
+
+        let investments = self.get_investments(handle).await?;
+        self.cache.insert(key, &investments);
+        Ok(investments)
+    }
+
+    /// Evicts every cached response for `handle`. Call this when
+    /// `/transactions/sync` (or any other out-of-band notification) reports new
+    /// data for the item, since cached reads would otherwise serve stale data
+    /// for up to `cache_ttl` longer.
+    pub fn invalidate_cache(&self, handle: &TokenHandle) {
+        self.cache.invalidate_token(&ResponseCache::token_hash(handle.id()));
+    }
+
+    fn cache_key(&self, handle: &TokenHandle, endpoint: &str, params: &str) -> (String, String, String) {
+        (ResponseCache::token_hash(handle.id()), endpoint.to_string(), params.to_string())
+    }
+
+    /// Calculate net worth from Plaid data, using cached balances/investments so
+    /// repeated calls don't fan out into fresh Plaid requests every time.
+    pub async fn calculate_net_worth(&self, handle: &TokenHandle) -> Result<f64> {
+        let accounts = self.get_balances_cached(handle, false).await?;
+        let investments = self.get_investments_cached(handle, false).await?;
 
         // Sum all account balances
         let account_total: f64 = accounts.iter()
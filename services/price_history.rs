@@ -0,0 +1,60 @@
+// Apollo Price History
+// Fetches daily closing prices per symbol so risk computations (covariance, Sharpe,
+// drawdown) are driven by real historical returns instead of placeholder constants.
+
+use anyhow::Result;
+use reqwest::Client;
+use serde::Deserialize;
+
+/// Trading days of history to request when none is specified -- about one year.
+pub const DEFAULT_LOOKBACK_DAYS: usize = 252;
+
+#[derive(Deserialize)]
+struct DailyCloseResponse {
+    closes: Vec<f64>,
+}
+
+/// Fetches daily closing prices from a REST endpoint templated on `{symbol}` and
+/// `{days}`.
+pub struct PriceHistory {
+    history_url_template: String,
+    client: Client,
+}
+
+impl PriceHistory {
+    pub fn new(history_url_template: impl Into<String>) -> Self {
+        Self { history_url_template: history_url_template.into(), client: Client::new() }
+    }
+
+    /// Fetch up to `lookback_days` of daily closing prices for `symbol`, oldest
+    /// first. Returns an empty vec (rather than erroring) when the provider has
+    /// no or too little history, so callers can fall back to class defaults.
+    pub async fn daily_closes(&self, symbol: &str, lookback_days: usize) -> Result<Vec<f64>> {
+        let url = self
+            .history_url_template
+            .replace("{symbol}", symbol)
+            .replace("{days}", &lookback_days.to_string());
+
+        let response = match self.client.get(&url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!(symbol, error = %e, "failed to reach price history provider");
+                return Ok(Vec::new());
+            }
+        };
+
+        match response.json::<DailyCloseResponse>().await {
+            Ok(parsed) => Ok(parsed.closes),
+            Err(e) => {
+                tracing::warn!(symbol, error = %e, "failed to parse price history response");
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// Daily log returns `r_t = ln(p_t / p_{t-1})` computed from closes, oldest
+    /// first.
+    pub fn log_returns(closes: &[f64]) -> Vec<f64> {
+        closes.windows(2).map(|w| (w[1] / w[0]).ln()).collect()
+    }
+}
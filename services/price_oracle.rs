@@ -0,0 +1,119 @@
+// Apollo Price Oracle
+// Resolves spot and historical prices across multiple providers with fallback, so a
+// single provider outage doesn't stall fills or historical backfills, and caches
+// historical lookups per (symbol, day) to avoid hammering providers during backfill.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    fn name(&self) -> &str;
+    async fn spot(&self, symbol: &str, asset_class: &str) -> Result<f64>;
+    async fn historical(&self, symbol: &str, timestamp: i64) -> Result<f64>;
+}
+
+/// Queries spot/historical prices from a REST endpoint templated on `{symbol}` and
+/// `{timestamp}`.
+pub struct HttpPriceProvider {
+    name: String,
+    spot_url_template: String,
+    historical_url_template: String,
+    client: Client,
+}
+
+impl HttpPriceProvider {
+    pub fn new(name: impl Into<String>, spot_url_template: impl Into<String>, historical_url_template: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            spot_url_template: spot_url_template.into(),
+            historical_url_template: historical_url_template.into(),
+            client: Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl PriceProvider for HttpPriceProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn spot(&self, symbol: &str, _asset_class: &str) -> Result<f64> {
+        let url = self.spot_url_template.replace("{symbol}", symbol);
+        let value: serde_json::Value = self.client.get(&url).send().await?.json().await?;
+        value.get("price").and_then(|p| p.as_f64()).context("missing price field in spot price response")
+    }
+
+    async fn historical(&self, symbol: &str, timestamp: i64) -> Result<f64> {
+        let url = self
+            .historical_url_template
+            .replace("{symbol}", symbol)
+            .replace("{timestamp}", &timestamp.to_string());
+        let value: serde_json::Value = self.client.get(&url).send().await?.json().await?;
+        value.get("price").and_then(|p| p.as_f64()).context("missing price field in historical price response")
+    }
+}
+
+/// Tries each configured provider in order, falling back to the next on error, and
+/// caches historical lookups by `(symbol, day)`.
+pub struct PriceOracle {
+    providers: Vec<Box<dyn PriceProvider>>,
+    historical_cache: Mutex<HashMap<(String, String), f64>>,
+}
+
+impl PriceOracle {
+    pub fn new(providers: Vec<Box<dyn PriceProvider>>) -> Self {
+        Self { providers, historical_cache: Mutex::new(HashMap::new()) }
+    }
+
+    pub async fn spot(&self, symbol: &str, asset_class: &str) -> Result<f64> {
+        let mut last_err = None;
+        for provider in &self.providers {
+            match provider.spot(symbol, asset_class).await {
+                Ok(price) => return Ok(price),
+                Err(e) => {
+                    tracing::warn!(provider = provider.name(), error = %e, "spot price provider failed, falling back");
+                    last_err = Some(e);
+                }
+            }
+        }
+        Err(last_err.unwrap_or_else(|| anyhow::anyhow!("no price providers configured")))
+            .with_context(|| format!("failed to resolve spot price for {}", symbol))
+    }
+
+    pub async fn historical(&self, symbol: &str, timestamp: i64) -> Result<f64> {
+        let day = day_bucket(timestamp);
+        let cache_key = (symbol.to_string(), day);
+
+        if let Some(price) = self.historical_cache.lock().unwrap().get(&cache_key) {
+            return Ok(*price);
+        }
+
+        let mut last_err = None;
+        for provider in &self.providers {
+            match provider.historical(symbol, timestamp).await {
+                Ok(price) => {
+                    self.historical_cache.lock().unwrap().insert(cache_key, price);
+                    return Ok(price);
+                }
+                Err(e) => {
+                    tracing::warn!(provider = provider.name(), error = %e, "historical price provider failed, falling back");
+                    last_err = Some(e);
+                }
+            }
+        }
+        Err(last_err.unwrap_or_else(|| anyhow::anyhow!("no price providers configured")))
+            .with_context(|| format!("failed to resolve historical price for {} at {}", symbol, timestamp))
+    }
+}
+
+fn day_bucket(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d")
+        .to_string()
+}
@@ -0,0 +1,112 @@
+// Apollo Rebalancer
+// Computes per-asset dollar deltas between current and target allocation across
+// crypto/stocks/NFTs and emits a concrete, ordered list of `StrategyAction`s --
+// sells before buys, so proceeds fund purchases instead of relying on margin.
+// Replaces the old single-scalar `calculate_drift` and its fixed 5% threshold with
+// a per-asset-class drift band.
+
+use crate::services::atlas_integration::StrategyAction;
+use std::collections::HashMap;
+
+/// One asset-class/symbol's current vs. target dollar value.
+#[derive(Debug, Clone)]
+pub struct AllocationLeg {
+    pub asset_class: String,
+    pub symbol: String,
+    pub current_value_usd: f64,
+    pub target_value_usd: f64,
+}
+
+impl AllocationLeg {
+    pub fn delta_usd(&self) -> f64 {
+        self.target_value_usd - self.current_value_usd
+    }
+}
+
+/// Concrete rebalance actions (sells ordered before buys) plus the dollar drift
+/// each leg was carrying at plan time, keyed by symbol.
+#[derive(Debug, Clone, Default)]
+pub struct RebalancePlan {
+    pub actions: Vec<StrategyAction>,
+    pub residual_drift: HashMap<String, f64>,
+}
+
+/// Outcome of executing a `RebalancePlan` leg-by-leg.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RebalanceOutcome {
+    Applied,
+    /// A leg failed partway through; `residual_drift` covers legs that never filled
+    /// (any already-filled sells that funded them were rolled back where possible).
+    PartiallyApplied { failed_leg_index: usize, residual_drift: HashMap<String, f64> },
+}
+
+/// Plans rebalances with a per-asset-class drift band, replacing the old fixed 5%
+/// threshold (e.g. crypto can be capped tighter than stocks).
+pub struct Rebalancer {
+    default_band: f64,
+    bands: HashMap<String, f64>,
+}
+
+impl Rebalancer {
+    pub fn new(default_band: f64) -> Self {
+        Self { default_band, bands: HashMap::new() }
+    }
+
+    pub fn with_band(mut self, asset_class: impl Into<String>, band: f64) -> Self {
+        self.bands.insert(asset_class.into(), band);
+        self
+    }
+
+    fn band_for(&self, asset_class: &str) -> f64 {
+        self.bands.get(asset_class).copied().unwrap_or(self.default_band)
+    }
+
+    /// True when a leg's drift exceeds its asset class's band.
+    pub fn needs_rebalance(&self, leg: &AllocationLeg) -> bool {
+        let total = leg.current_value_usd.max(leg.target_value_usd);
+        if total < f64::EPSILON {
+            return false;
+        }
+        (leg.delta_usd().abs() / total) > self.band_for(&leg.asset_class)
+    }
+
+    /// Computes per-leg dollar deltas and emits a concrete, ordered action list:
+    /// every sell (negative delta) first, so proceeds are realized before buys
+    /// (positive delta) are placed.
+    pub fn plan(&self, legs: &[AllocationLeg]) -> RebalancePlan {
+        let mut sells = Vec::new();
+        let mut buys = Vec::new();
+        let mut residual_drift = HashMap::new();
+
+        for leg in legs {
+            if !self.needs_rebalance(leg) {
+                continue;
+            }
+
+            let delta = leg.delta_usd();
+            residual_drift.insert(leg.symbol.clone(), delta);
+
+            let action = StrategyAction {
+                action_type: if delta < 0.0 { "sell" } else { "buy" }.to_string(),
+                asset_class: leg.asset_class.clone(),
+                symbol: leg.symbol.clone(),
+                side: if delta < 0.0 { "sell" } else { "buy" }.to_string(),
+                amount: delta.abs(),
+                reason: "Rebalance to target allocation".to_string(),
+                priority: 1,
+                trigger: None,
+            };
+
+            if delta < 0.0 {
+                sells.push(action);
+            } else {
+                buys.push(action);
+            }
+        }
+
+        let mut actions = sells;
+        actions.extend(buys);
+
+        RebalancePlan { actions, residual_drift }
+    }
+}
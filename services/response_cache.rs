@@ -0,0 +1,57 @@
+// Apollo Response Cache
+// TTL-bounded cache for PlaidClient responses, keyed by (access-token hash,
+// endpoint, params) so repeated calls against the same account/endpoint/params
+// don't re-hit Plaid's rate limits within the TTL window. Backed by a `DashMap`
+// so concurrent readers/writers across requests don't contend on a single lock.
+
+use dashmap::DashMap;
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::{Duration, Instant};
+
+/// `(access_token_hash, endpoint, params)`.
+pub type CacheKey = (String, String, String);
+
+struct CacheEntry {
+    value: serde_json::Value,
+    inserted_at: Instant,
+}
+
+#[derive(Default)]
+pub struct ResponseCache {
+    entries: DashMap<CacheKey, CacheEntry>,
+}
+
+impl ResponseCache {
+    pub fn new() -> Self {
+        Self { entries: DashMap::new() }
+    }
+
+    /// Hashes a token identity so the cache key never carries anything secret,
+    /// even though handles are already opaque.
+    pub fn token_hash(token_identity: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(token_identity.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &CacheKey, ttl: Duration) -> Option<T> {
+        let entry = self.entries.get(key)?;
+        if entry.inserted_at.elapsed() > ttl {
+            return None;
+        }
+        serde_json::from_value(entry.value.clone()).ok()
+    }
+
+    pub fn insert<T: Serialize>(&self, key: CacheKey, value: &T) {
+        if let Ok(value) = serde_json::to_value(value) {
+            self.entries.insert(key, CacheEntry { value, inserted_at: Instant::now() });
+        }
+    }
+
+    /// Evicts every cached entry for a given access token, e.g. once
+    /// `/transactions/sync` reports new data and cached reads would now be stale.
+    pub fn invalidate_token(&self, token_hash: &str) {
+        self.entries.retain(|(hash, _, _), _| hash != token_hash);
+    }
+}
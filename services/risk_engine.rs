@@ -0,0 +1,183 @@
+// Apollo Risk Engine
+// Covariance-based portfolio risk -- annualized volatility, Sharpe ratio, parametric
+// VaR, and max drawdown -- computed from each asset's daily log-return series
+// instead of a single linear function of crypto weight.
+
+const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+const VAR_Z_SCORE_95: f64 = 1.645; // one-tailed 95% normal quantile
+const RISK_FREE_RATE: f64 = 0.04;
+/// Below this many daily observations a history is too short to estimate
+/// covariance from; fall back to an uncorrelated asset-class default variance.
+const MIN_HISTORY_LEN: usize = 20;
+
+/// One asset's allocation weight and (possibly missing/short) daily log-return
+/// history, as fed into `assess_portfolio_risk`.
+#[derive(Debug, Clone)]
+pub struct AssetReturns {
+    pub symbol: String,
+    pub asset_class: String,
+    pub weight: f64,
+    /// Daily log returns, oldest first.
+    pub daily_log_returns: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PortfolioRisk {
+    pub volatility: f64,
+    pub sharpe_ratio: f64,
+    pub max_drawdown: f64,
+    pub var_95: f64,
+}
+
+/// Annualized variance assumed for an asset class when its price history is
+/// missing or too short to estimate covariance from.
+fn default_variance(asset_class: &str) -> f64 {
+    match asset_class {
+        "crypto" => 0.64, // ~80% annualized vol
+        "stocks" => 0.04, // ~20% annualized vol
+        "nfts" => 1.00,   // ~100% annualized vol
+        _ => 0.01,        // cash and cash-like holdings
+    }
+}
+
+/// Computes portfolio risk for a weighted basket of assets: `wᵀΣw` for variance,
+/// `sqrt(252 · wᵀΣw)` for annualized volatility, `(wᵀμ − r_f) / vol` for Sharpe,
+/// `1.645 · vol · total_value` for parametric 95% VaR, and max drawdown from the
+/// cumulative weighted-return series.
+pub fn assess_portfolio_risk(assets: &[AssetReturns], total_value: f64) -> PortfolioRisk {
+    let weights: Vec<f64> = assets.iter().map(|a| a.weight).collect();
+    let cov = covariance_matrix(assets);
+    let mean_returns = annualized_mean_returns(assets);
+
+    let portfolio_variance = dot(&weights, &mat_vec(&cov, &weights)).max(0.0);
+    let volatility = portfolio_variance.sqrt();
+
+    let expected_return = dot(&weights, &mean_returns);
+    let sharpe_ratio = if volatility > f64::EPSILON { (expected_return - RISK_FREE_RATE) / volatility } else { 0.0 };
+
+    let var_95 = VAR_Z_SCORE_95 * volatility * total_value.max(0.0);
+    let max_drawdown = max_drawdown_from_weighted_returns(assets, &weights);
+
+    PortfolioRisk { volatility, sharpe_ratio, max_drawdown, var_95 }
+}
+
+/// Builds the N x N sample covariance matrix of annualized daily log returns using
+/// pairwise-complete observations, so assets with unequal-length histories don't
+/// need to be discarded -- each pair's covariance uses only their overlapping tail.
+/// Assets with too little history fall back to an uncorrelated class-default
+/// variance on the diagonal.
+fn covariance_matrix(assets: &[AssetReturns]) -> Vec<Vec<f64>> {
+    let n = assets.len();
+    let mut cov = vec![vec![0.0; n]; n];
+
+    for i in 0..n {
+        for j in i..n {
+            let value = if assets[i].daily_log_returns.len() < MIN_HISTORY_LEN || assets[j].daily_log_returns.len() < MIN_HISTORY_LEN {
+                if i == j { default_variance(&assets[i].asset_class) } else { 0.0 }
+            } else {
+                sample_covariance(&assets[i].daily_log_returns, &assets[j].daily_log_returns) * TRADING_DAYS_PER_YEAR
+            };
+            cov[i][j] = value;
+            cov[j][i] = value;
+        }
+    }
+
+    clamp_positive_semidefinite(cov)
+}
+
+/// Pairwise-complete sample covariance of two return series, aligned on their
+/// common (most recent) overlap rather than requiring equal-length histories.
+fn sample_covariance(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len().min(b.len());
+    if n < 2 {
+        return 0.0;
+    }
+    let a = &a[a.len() - n..];
+    let b = &b[b.len() - n..];
+
+    let mean_a = a.iter().sum::<f64>() / n as f64;
+    let mean_b = b.iter().sum::<f64>() / n as f64;
+
+    a.iter().zip(b.iter()).map(|(x, y)| (x - mean_a) * (y - mean_b)).sum::<f64>() / (n as f64 - 1.0)
+}
+
+/// Guards against a non-PSD matrix from noisy pairwise-complete covariances by
+/// nudging the diagonal up until every 2x2 sub-determinant is non-negative -- a
+/// cheap stand-in for clamping negative eigenvalues that avoids a full
+/// eigendecomposition.
+fn clamp_positive_semidefinite(mut cov: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let n = cov.len();
+    for i in 0..n {
+        for j in 0..n {
+            if i == j {
+                continue;
+            }
+            let det_2x2 = cov[i][i] * cov[j][j] - cov[i][j] * cov[j][i];
+            if det_2x2 < 0.0 {
+                let epsilon = (cov[i][j] * cov[j][i] - cov[i][i] * cov[j][j]).sqrt().max(1e-6);
+                cov[i][i] += epsilon;
+                cov[j][j] += epsilon;
+            }
+        }
+    }
+    cov
+}
+
+/// Annualized mean log-return per asset; falls back to 0 for assets without
+/// sufficient history.
+fn annualized_mean_returns(assets: &[AssetReturns]) -> Vec<f64> {
+    assets
+        .iter()
+        .map(|a| {
+            if a.daily_log_returns.len() < MIN_HISTORY_LEN {
+                0.0
+            } else {
+                let mean_daily = a.daily_log_returns.iter().sum::<f64>() / a.daily_log_returns.len() as f64;
+                mean_daily * TRADING_DAYS_PER_YEAR
+            }
+        })
+        .collect()
+}
+
+/// Max drawdown of the cumulative weighted daily return series:
+/// `max over t of (peak_so_far − value_t) / peak_so_far`. Assets without enough
+/// history to estimate anything from (e.g. NFTs/cash with no price feed) are
+/// excluded from the series rather than collapsing the shared window to their
+/// `0`-length history -- the same `MIN_HISTORY_LEN` cutoff `covariance_matrix`
+/// and `annualized_mean_returns` already use for the same reason.
+fn max_drawdown_from_weighted_returns(assets: &[AssetReturns], weights: &[f64]) -> f64 {
+    let priced: Vec<(&AssetReturns, f64)> = assets
+        .iter()
+        .zip(weights.iter().copied())
+        .filter(|(a, _)| a.daily_log_returns.len() >= MIN_HISTORY_LEN)
+        .collect();
+
+    let len = priced.iter().map(|(a, _)| a.daily_log_returns.len()).min().unwrap_or(0);
+    if len == 0 {
+        return 0.0;
+    }
+
+    let mut cumulative = 1.0;
+    let mut peak = 1.0;
+    let mut max_drawdown = 0.0;
+
+    for t in 0..len {
+        let daily_portfolio_return: f64 = priced
+            .iter()
+            .map(|(a, w)| w * a.daily_log_returns[a.daily_log_returns.len() - len + t])
+            .sum();
+        cumulative *= 1.0 + daily_portfolio_return;
+        peak = peak.max(cumulative);
+        max_drawdown = max_drawdown.max((peak - cumulative) / peak);
+    }
+
+    max_drawdown
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+fn mat_vec(mat: &[Vec<f64>], v: &[f64]) -> Vec<f64> {
+    mat.iter().map(|row| dot(row, v)).collect()
+}
@@ -4,7 +4,11 @@
 use crate::services::atlas_integration::{
     AtlasClient, CompleteFinancialProfile, FinancialGoal, ApolloStrategy, StrategyAction
 };
+use crate::services::allocation_policy::AllocationPolicy;
 use crate::services::delt_client::DeltClient;
+use crate::services::price_history::{PriceHistory, DEFAULT_LOOKBACK_DAYS};
+use crate::services::risk_engine::{self, AssetReturns};
+use crate::services::triggers::Trigger;
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -14,6 +18,7 @@ pub struct StrategyGenerator {
     atlas: AtlasClient,
     delt: DeltClient,
     ai_endpoint: String,  // Ollama or OpenAI endpoint
+    price_history: PriceHistory,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,11 +57,12 @@ pub struct RiskAssessment {
 }
 
 impl StrategyGenerator {
-    pub fn new(atlas: AtlasClient, delt: DeltClient, ai_endpoint: String) -> Self {
+    pub fn new(atlas: AtlasClient, delt: DeltClient, ai_endpoint: String, price_history: PriceHistory) -> Self {
         Self {
             atlas,
             delt,
             ai_endpoint,
+            price_history,
         }
     }
 
@@ -74,7 +80,7 @@ impl StrategyGenerator {
         let stock_portfolio = self.get_stock_positions(user_id).await?;
 
         // 4. Analyze current vs target
-        let analysis = self.analyze_portfolio(&profile, &delt_portfolio, &stock_portfolio)?;
+        let analysis = self.analyze_portfolio(&profile, &delt_portfolio, &stock_portfolio).await?;
 
         // 5. Generate strategy using AI
         let strategy = self.generate_ai_strategy(&profile, &analysis).await?;
@@ -86,7 +92,7 @@ impl StrategyGenerator {
     }
 
     /// Analyze portfolio and calculate gaps
-    fn analyze_portfolio(
+    async fn analyze_portfolio(
         &self,
         profile: &CompleteFinancialProfile,
         crypto_positions: &serde_json::Value,
@@ -125,7 +131,7 @@ impl StrategyGenerator {
         let required_return = self.calculate_required_return(profile)?;
 
         // Assess risk
-        let risk_assessment = self.assess_risk(&current, &profile)?;
+        let risk_assessment = self.assess_risk(&current, &profile).await?;
 
         Ok(PortfolioAnalysis {
             current_allocation: current,
@@ -136,7 +142,11 @@ impl StrategyGenerator {
         })
     }
 
-    /// Determine target allocation based on user goals and risk tolerance
+    /// Determine target allocation based on user goals, risk tolerance, and which
+    /// asset classes can actually be priced. A class with no reliable price
+    /// source is disabled entirely rather than targeted -- its weight is
+    /// redistributed across the rest -- so the generator never proposes a trade
+    /// in an asset it can't price.
     fn determine_target_allocation(
         &self,
         profile: &CompleteFinancialProfile,
@@ -146,31 +156,41 @@ impl StrategyGenerator {
             .find(|g| g.status == "active")
             .context("No active goals found")?;
 
-        // Determine allocation based on risk tolerance
-        let (stocks_pct, crypto_pct, nfts_pct, cash_pct) = match primary_goal.risk_tolerance.as_str() {
-            "conservative" => (0.40, 0.10, 0.05, 0.45),
-            "moderate" => (0.50, 0.30, 0.10, 0.10),
-            "aggressive" => (0.40, 0.45, 0.10, 0.05),
-            _ => (0.50, 0.30, 0.10, 0.10),
-        };
+        let mut policy = AllocationPolicy::for_risk_tolerance(&primary_goal.risk_tolerance);
+        if !self.has_nft_price_source() {
+            policy = policy.disable_class("nfts");
+        }
+        let weights = policy.resolve_weights();
 
         // Calculate total target value
         let total_target = profile.summary.total_goal_value;
 
         Ok(AllocationBreakdown {
-            stocks: total_target * stocks_pct,
-            crypto: total_target * crypto_pct,
-            nfts: total_target * nfts_pct,
-            cash: total_target * cash_pct,
+            stocks: total_target * weights.get("stocks").copied().unwrap_or(0.0),
+            crypto: total_target * weights.get("crypto").copied().unwrap_or(0.0),
+            nfts: total_target * weights.get("nfts").copied().unwrap_or(0.0),
+            cash: total_target * weights.get("cash").copied().unwrap_or(0.0),
             total: total_target,
         })
     }
 
-    /// Calculate required annual return to reach goals
+    /// No NFT pricing service is wired up yet (see `sum_position_values`'s NFT
+    /// TODO), so NFTs can't be priced and the policy excludes them.
+    fn has_nft_price_source(&self) -> bool {
+        false
+    }
+
+    /// Calculate the required annual return to reach goals by solving the
+    /// future-value-of-annuity equation `FV = PV·(1+r)^n + PMT·(((1+r)^n − 1)/r)`
+    /// for `r`, rather than a bare compound-rate formula that ignores ongoing
+    /// contributions.
     fn calculate_required_return(&self, profile: &CompleteFinancialProfile) -> Result<f64> {
         let total_target = profile.summary.total_goal_value;
         let total_current = profile.summary.total_current_value;
-        let gap = total_target - total_current;
+
+        if total_current >= total_target {
+            return Ok(0.0); // Already funded
+        }
 
         // Get primary goal for timeline
         let primary_goal = profile.goals.iter()
@@ -180,23 +200,16 @@ impl StrategyGenerator {
         let years_remaining = self.years_until(primary_goal.target_date);
 
         if years_remaining <= 0.0 {
-            return Ok(0.0);
+            // The funded case already returned above, so reaching here means the
+            // deadline has passed and the goal is still unfunded -- mathematically
+            // unreachable, not "no further return needed".
+            return Ok(MAX_RATE);
         }
 
-        // Simple calculation: gap / (years * monthly_contribution * 12)
         let monthly_contrib = primary_goal.monthly_contribution.unwrap_or(0.0);
-        let total_contributions = monthly_contrib * 12.0 * years_remaining;
+        let annual_contribution = monthly_contrib * 12.0;
 
-        let growth_needed = gap - total_contributions;
-
-        if total_current > 0.0 {
-            // FV = PV * (1 + r)^n
-            // r = (FV/PV)^(1/n) - 1
-            let required_return = (total_target / total_current).powf(1.0 / years_remaining) - 1.0;
-            Ok(required_return)
-        } else {
-            Ok(0.08) // Default 8% if no current amount
-        }
+        Ok(solve_annuity_rate(total_current, annual_contribution, total_target, years_remaining))
     }
 
     fn years_until(&self, target_date: DateTime<Utc>) -> f64 {
@@ -211,24 +224,57 @@ impl StrategyGenerator {
         Ok(0.0)
     }
 
-    /// Assess portfolio risk
-    fn assess_risk(
+    /// Assess portfolio risk from real historical returns: builds a covariance
+    /// matrix of daily log returns across the allocation's asset classes and
+    /// derives volatility, Sharpe ratio, parametric VaR, and max drawdown from it.
+    async fn assess_risk(
         &self,
         allocation: &AllocationBreakdown,
-        profile: &CompleteFinancialProfile,
+        _profile: &CompleteFinancialProfile,
     ) -> Result<RiskAssessment> {
-        // Simplified risk calculation
-        // In production, would use historical returns, correlations, etc.
+        if allocation.total <= 0.0 {
+            return Ok(RiskAssessment { volatility: 0.0, sharpe_ratio: 0.0, max_drawdown: 0.0, var_95: 0.0, risk_score: 0.0 });
+        }
+
+        let stocks_closes = self.price_history.daily_closes("SPY", DEFAULT_LOOKBACK_DAYS).await?;
+        let crypto_closes = self.price_history.daily_closes("BTC", DEFAULT_LOOKBACK_DAYS).await?;
 
-        let crypto_weight = allocation.crypto / allocation.total;
-        let volatility = 0.20 + (crypto_weight * 0.60); // Higher crypto = higher volatility
+        let assets = vec![
+            AssetReturns {
+                symbol: "SPY".to_string(),
+                asset_class: "stocks".to_string(),
+                weight: allocation.stocks / allocation.total,
+                daily_log_returns: PriceHistory::log_returns(&stocks_closes),
+            },
+            AssetReturns {
+                symbol: "BTC".to_string(),
+                asset_class: "crypto".to_string(),
+                weight: allocation.crypto / allocation.total,
+                daily_log_returns: PriceHistory::log_returns(&crypto_closes),
+            },
+            AssetReturns {
+                symbol: "NFTS".to_string(),
+                asset_class: "nfts".to_string(),
+                weight: allocation.nfts / allocation.total,
+                daily_log_returns: Vec::new(), // no price history source yet, falls back to class default variance
+            },
+            AssetReturns {
+                symbol: "CASH".to_string(),
+                asset_class: "cash".to_string(),
+                weight: allocation.cash / allocation.total,
+                daily_log_returns: Vec::new(),
+            },
+        ];
+
+        let risk = risk_engine::assess_portfolio_risk(&assets, allocation.total);
 
         Ok(RiskAssessment {
-            volatility,
-            sharpe_ratio: 1.2,  // Placeholder
-            max_drawdown: 0.30,
-            var_95: 0.15,
-            risk_score: crypto_weight * 10.0,
+            volatility: risk.volatility,
+            sharpe_ratio: risk.sharpe_ratio,
+            max_drawdown: risk.max_drawdown,
+            var_95: risk.var_95,
+            // Maps annualized volatility onto a 0-10 scale; 50% vol saturates it.
+            risk_score: (risk.volatility * 20.0).min(10.0),
         })
     }
 
@@ -241,6 +287,13 @@ impl StrategyGenerator {
         // Generate actions to close the gap
         let mut actions = Vec::new();
 
+        // Reducing crypto exposure is the signal to attach downside protection: a
+        // stop-loss on the BTC sell itself, and a limit entry into SPY so the
+        // freed-up cash only buys in on a dip rather than at the current price.
+        let reducing_crypto = analysis.gap.crypto_diff < 0.0;
+        let btc_last_close = self.price_history.daily_closes("BTC", 1).await.unwrap_or_default().last().copied();
+        let spy_last_close = self.price_history.daily_closes("SPY", 1).await.unwrap_or_default().last().copied();
+
         // Add rebalancing actions
         if analysis.gap.stocks_diff.abs() > 1000.0 {
             actions.push(StrategyAction {
@@ -251,6 +304,11 @@ impl StrategyGenerator {
                 amount: analysis.gap.stocks_diff.abs(),
                 reason: format!("Rebalance to target allocation"),
                 priority: 1,
+                trigger: if analysis.gap.stocks_diff > 0.0 && reducing_crypto {
+                    spy_last_close.map(|price| Trigger::PriceBelow { threshold: price * 0.98 })
+                } else {
+                    None
+                },
             });
         }
 
@@ -263,6 +321,11 @@ impl StrategyGenerator {
                 amount: analysis.gap.crypto_diff.abs() * 0.6,
                 reason: format!("Increase crypto exposure"),
                 priority: 2,
+                trigger: if reducing_crypto {
+                    btc_last_close.map(|price| Trigger::PriceBelow { threshold: price * 0.90 })
+                } else {
+                    None
+                },
             });
 
             actions.push(StrategyAction {
@@ -273,6 +336,7 @@ impl StrategyGenerator {
                 amount: analysis.gap.crypto_diff.abs() * 0.4,
                 reason: "Platform token with high growth potential".to_string(),
                 priority: 3,
+                trigger: None,
             });
         }
 
@@ -303,4 +367,57 @@ impl StrategyGenerator {
     }
 }
 
+/// Future value of `pv` growing at annual rate `r` for `n` years, plus an
+/// end-of-year annuity of `pmt` per year: `PV·(1+r)^n + PMT·(((1+r)^n − 1)/r)`.
+/// Takes the `r → 0` limit (`PMT·n`) rather than dividing by zero.
+fn future_value_of_annuity(pv: f64, pmt: f64, r: f64, n: f64) -> f64 {
+    let growth = (1.0 + r).powf(n);
+    if r.abs() < 1e-9 {
+        pv + pmt * n
+    } else {
+        pv * growth + pmt * ((growth - 1.0) / r)
+    }
+}
+
+/// Bisection bracket for `solve_annuity_rate`. `MAX_RATE` also doubles as the
+/// clamped "goal is mathematically unreachable" rate `calculate_required_return`
+/// returns once a goal's deadline has already passed and it's still unfunded.
+const MIN_RATE: f64 = -0.5;
+const MAX_RATE: f64 = 1.0;
+
+/// Solves `future_value_of_annuity(pv, pmt, r, n) == target` for `r` by bisection
+/// over `[-0.5, 1.0]`, since the annuity equation has no closed-form solution for
+/// `r`. Clamps to the bracket's endpoints when the goal is unreachable even at
+/// +100% annual growth, or overshot even at -50%.
+fn solve_annuity_rate(pv: f64, pmt: f64, target: f64, n: f64) -> f64 {
+    const TOLERANCE: f64 = 1e-6;
+    const MAX_ITERATIONS: usize = 200;
+
+    let f = |r: f64| future_value_of_annuity(pv, pmt, r, n) - target;
+
+    let (mut lo, mut hi) = (MIN_RATE, MAX_RATE);
+    if f(hi) < 0.0 {
+        return MAX_RATE;
+    }
+    if f(lo) > 0.0 {
+        return MIN_RATE;
+    }
+
+    let mut mid = 0.0;
+    for _ in 0..MAX_ITERATIONS {
+        mid = (lo + hi) / 2.0;
+        let f_mid = f(mid);
+        if f_mid.abs() < TOLERANCE {
+            break;
+        }
+        if f_mid > 0.0 {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+
+    mid
+}
+
 
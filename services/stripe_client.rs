@@ -1,14 +1,30 @@
 // Apollo Stripe Client
 // Payment processing for platform subscriptions and deposits
 
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use anyhow::{Result, Context};
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::services::transport::{Transport, TransportBuilder, TransportLayer, TransportRequest};
 
 /// Stripe Payment Client
 pub struct StripeClient {
-    api_key: String,
-    client: Client,
+    transport: Arc<dyn Transport>,
+}
+
+pub struct StripeClientBuilder {
+    transport: TransportBuilder,
+}
+
+impl StripeClientBuilder {
+    pub fn with(mut self, layer: impl TransportLayer + 'static) -> Self {
+        self.transport = self.transport.with(layer);
+        self
+    }
+
+    pub fn build(self) -> StripeClient {
+        StripeClient { transport: self.transport.build() }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,49 +55,37 @@ pub struct Customer {
 
 impl StripeClient {
     pub fn new(api_key: String) -> Self {
-        Self {
-            api_key,
-            client: Client::new(),
-        }
+        Self::builder(api_key).build()
+    }
+
+    /// Compose a `StripeClient` with a custom transport stack, e.g.
+    /// `StripeClient::builder(key).with(RetryMiddleware::default()).build()`.
+    pub fn builder(api_key: String) -> StripeClientBuilder {
+        StripeClientBuilder { transport: TransportBuilder::new(api_key) }
     }
 
     /// Create payment intent
     pub async fn create_payment_intent(&self, amount: i64, currency: &str) -> Result<PaymentIntent> {
-        let url = "https://api.stripe.com/v1/payment_intents";
-
-        let params = [
-            ("amount", amount.to_string()),
-            ("currency", currency.to_string()),
-        ];
-
-        let response = self.client
-            .post(url)
-            .bearer_auth(&self.api_key)
-            .form(&params)
-            .send()
-            .await?;
+        let request = TransportRequest::post_form(
+            "https://api.stripe.com/v1/payment_intents",
+            vec![("amount".to_string(), amount.to_string()), ("currency".to_string(), currency.to_string())],
+        );
 
-        let intent: PaymentIntent = response.json().await?;
+        let value = self.transport.send(request).await?;
+        let intent: PaymentIntent = serde_json::from_value(value)?;
         Ok(intent)
     }
 
     /// Create customer
     pub async fn create_customer(&self, email: &str, name: Option<&str>) -> Result<Customer> {
-        let url = "https://api.stripe.com/v1/customers";
-
-        let mut params = vec![("email", email.to_string())];
+        let mut form = vec![("email".to_string(), email.to_string())];
         if let Some(n) = name {
-            params.push(("name", n.to_string()));
+            form.push(("name".to_string(), n.to_string()));
         }
+        let request = TransportRequest::post_form("https://api.stripe.com/v1/customers", form);
 
-        let response = self.client
-            .post(url)
-            .bearer_auth(&self.api_key)
-            .form(&params)
-            .send()
-            .await?;
-
-        let customer: Customer = response.json().await?;
+        let value = self.transport.send(request).await?;
+        let customer: Customer = serde_json::from_value(value)?;
         Ok(customer)
     }
 
@@ -91,35 +95,20 @@ impl StripeClient {
         customer_id: &str,
         price_id: &str,
     ) -> Result<Subscription> {
-        let url = "https://api.stripe.com/v1/subscriptions";
+        let request = TransportRequest::post_form(
+            "https://api.stripe.com/v1/subscriptions",
+            vec![("customer".to_string(), customer_id.to_string()), ("items[0][price]".to_string(), price_id.to_string())],
+        );
 
-        let params = [
-            ("customer", customer_id.to_string()),
-            ("items[0][price]", price_id.to_string()),
-        ];
-
-        let response = self.client
-            .post(url)
-            .bearer_auth(&self.api_key)
-            .form(&params)
-            .send()
-            .await?;
-
-        let subscription: Subscription = response.json().await?;
+        let value = self.transport.send(request).await?;
+        let subscription: Subscription = serde_json::from_value(value)?;
         Ok(subscription)
     }
 
     /// Cancel subscription
     pub async fn cancel_subscription(&self, subscription_id: &str) -> Result<()> {
         let url = format!("https://api.stripe.com/v1/subscriptions/{}", subscription_id);
-
-        self.client
-            .delete(&url)
-            .bearer_auth(&self.api_key)
-            .send()
-            .await?;
-
+        self.transport.send(TransportRequest::delete(url)).await?;
         Ok(())
     }
 }
-
@@ -0,0 +1,119 @@
+// Apollo Token Vault
+// Encrypts Plaid access tokens at rest with ChaCha20Poly1305 so `PlaidClient` and its
+// callers handle opaque handles instead of long-lived bank credentials, the same way
+// wallet crates protect spending material -- raw tokens never live in logs or plain
+// serde structs.
+
+use anyhow::{Context, Result};
+use chacha20poly1305::aead::{rand_core::RngCore, Aead, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Opaque reference to an encrypted access token; the plaintext never leaves
+/// `TokenVault::with_token`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenHandle(String);
+
+impl TokenHandle {
+    /// Stable, non-secret identity for this handle (the `item_id` it was stored
+    /// under) -- safe to use as a cache key or log field, unlike the token itself.
+    pub fn id(&self) -> &str {
+        &self.0
+    }
+}
+
+struct SealedToken {
+    nonce: [u8; 12],
+    ciphertext: Vec<u8>,
+}
+
+/// Encrypts access tokens at rest, keyed by `item_id`. The encryption key never
+/// leaves the vault; callers only ever see a `TokenHandle`.
+pub struct TokenVault {
+    cipher: RwLock<ChaCha20Poly1305>,
+    sealed: RwLock<HashMap<String, SealedToken>>,
+}
+
+impl TokenVault {
+    /// `key` is a 32-byte key already derived from a master secret (e.g. via a KDF
+    /// upstream); the vault itself doesn't do key derivation.
+    pub fn new(key: [u8; 32]) -> Self {
+        Self {
+            cipher: RwLock::new(ChaCha20Poly1305::new(Key::from_slice(&key))),
+            sealed: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Encrypts `access_token` under a fresh nonce and stores it keyed by `item_id`,
+    /// returning an opaque handle. The plaintext is not retained by the vault.
+    pub fn store_token(&self, item_id: &str, access_token: &str) -> Result<TokenHandle> {
+        let mut nonce_bytes = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce_bytes);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let ciphertext = self
+            .cipher
+            .read()
+            .unwrap()
+            .encrypt(nonce, access_token.as_bytes())
+            .map_err(|e| anyhow::anyhow!("failed to encrypt access token: {}", e))?;
+
+        self.sealed.write().unwrap().insert(item_id.to_string(), SealedToken { nonce: nonce_bytes, ciphertext });
+        Ok(TokenHandle(item_id.to_string()))
+    }
+
+    /// Decrypts the token for `handle`, hands the plaintext to `f`, and drops it
+    /// immediately after -- it only exists transiently on the stack during a call.
+    pub fn with_token<R>(&self, handle: &TokenHandle, f: impl FnOnce(&str) -> R) -> Result<R> {
+        let sealed = self.sealed.read().unwrap();
+        let entry = sealed.get(&handle.0).context("unknown token handle")?;
+        let nonce = Nonce::from_slice(&entry.nonce);
+
+        let plaintext = self
+            .cipher
+            .read()
+            .unwrap()
+            .decrypt(nonce, entry.ciphertext.as_ref())
+            .map_err(|e| anyhow::anyhow!("failed to decrypt access token: {}", e))?;
+        let token = String::from_utf8(plaintext).context("decrypted token was not valid utf-8")?;
+
+        Ok(f(&token))
+    }
+
+    /// Re-encrypts every stored token under a new key, e.g. for periodic key
+    /// rotation; callers' `TokenHandle`s remain valid.
+    pub fn rotate(&self, new_key: [u8; 32]) -> Result<()> {
+        let new_cipher = ChaCha20Poly1305::new(Key::from_slice(&new_key));
+        let mut sealed = self.sealed.write().unwrap();
+        let old_cipher = self.cipher.read().unwrap();
+
+        // Read `sealed` rather than draining it, and only swap the rotated map in
+        // once every entry has converted successfully. A `HashMap::drain()` whose
+        // iterator is dropped early (as a `?`-triggered return from inside the loop
+        // would do) silently discards every remaining un-yielded entry, so an
+        // in-place drain-and-return-on-error here would destroy the rest of the
+        // vault the moment one token failed to decrypt or re-encrypt.
+        let mut rotated = HashMap::with_capacity(sealed.len());
+        for (item_id, entry) in sealed.iter() {
+            let nonce = Nonce::from_slice(&entry.nonce);
+            let plaintext = old_cipher
+                .decrypt(nonce, entry.ciphertext.as_ref())
+                .map_err(|e| anyhow::anyhow!("failed to decrypt {} during rotation: {}", item_id, e))?;
+
+            let mut new_nonce_bytes = [0u8; 12];
+            OsRng.fill_bytes(&mut new_nonce_bytes);
+            let new_nonce = Nonce::from_slice(&new_nonce_bytes);
+            let ciphertext = new_cipher
+                .encrypt(new_nonce, plaintext.as_ref())
+                .map_err(|e| anyhow::anyhow!("failed to re-encrypt {} during rotation: {}", item_id, e))?;
+
+            rotated.insert(item_id.clone(), SealedToken { nonce: new_nonce_bytes, ciphertext });
+        }
+        drop(old_cipher);
+
+        *sealed = rotated;
+        *self.cipher.write().unwrap() = new_cipher;
+        Ok(())
+    }
+}
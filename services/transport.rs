@@ -0,0 +1,325 @@
+// Apollo Transport
+// A stackable request middleware layer shared by DeltClient, UnifiedBrokerageClient,
+// and StripeClient, following the tower::Layer / ethers-rs Middleware pattern: each
+// layer wraps an inner `Transport` and can inspect, retry, throttle, or annotate the
+// request before delegating to it. Concrete clients hold an `Arc<dyn Transport>`
+// instead of a bare `reqwest::Client` and compose their policy at construction time.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use rand::Rng;
+use reqwest::Client;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::instrument;
+
+/// Per-request timeout applied when a client doesn't configure one explicitly.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportMethod {
+    Get,
+    Post,
+    Delete,
+}
+
+/// An outbound request, independent of how its body is ultimately encoded.
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+    pub method: TransportMethod,
+    pub url: String,
+    pub json_body: Option<serde_json::Value>,
+    pub form_body: Option<Vec<(String, String)>>,
+    pub idempotency_key: Option<String>,
+}
+
+impl TransportRequest {
+    pub fn get(url: impl Into<String>) -> Self {
+        Self { method: TransportMethod::Get, url: url.into(), json_body: None, form_body: None, idempotency_key: None }
+    }
+
+    pub fn post_json(url: impl Into<String>, body: &impl Serialize) -> Result<Self> {
+        Ok(Self {
+            method: TransportMethod::Post,
+            url: url.into(),
+            json_body: Some(serde_json::to_value(body)?),
+            form_body: None,
+            idempotency_key: None,
+        })
+    }
+
+    pub fn post_form(url: impl Into<String>, form: Vec<(String, String)>) -> Self {
+        Self { method: TransportMethod::Post, url: url.into(), json_body: None, form_body: Some(form), idempotency_key: None }
+    }
+
+    pub fn delete(url: impl Into<String>) -> Self {
+        Self { method: TransportMethod::Delete, url: url.into(), json_body: None, form_body: None, idempotency_key: None }
+    }
+
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+}
+
+/// Sends a `TransportRequest` and returns the decoded JSON response. Implemented by
+/// the base HTTP transport and by every middleware layer wrapping one.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(&self, request: TransportRequest) -> Result<serde_json::Value>;
+}
+
+/// Base transport: a bearer-authenticated `reqwest::Client` hitting `base_url`, with a
+/// fixed per-request timeout and status/body-aware error reporting.
+pub struct HttpTransport {
+    auth_header: String,
+    client: Client,
+}
+
+impl HttpTransport {
+    pub fn new(bearer_token: impl Into<String>, timeout: Duration) -> Self {
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("reqwest client config is valid");
+        Self { auth_header: format!("Bearer {}", bearer_token.into()), client }
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn send(&self, request: TransportRequest) -> Result<serde_json::Value> {
+        let mut builder = match request.method {
+            TransportMethod::Get => self.client.get(&request.url),
+            TransportMethod::Post => self.client.post(&request.url),
+            TransportMethod::Delete => self.client.delete(&request.url),
+        };
+
+        builder = builder.header("Authorization", &self.auth_header);
+        if let Some(key) = &request.idempotency_key {
+            builder = builder.header("Idempotency-Key", key);
+        }
+        if let Some(json) = &request.json_body {
+            builder = builder.json(json);
+        } else if let Some(form) = &request.form_body {
+            builder = builder.form(form);
+        }
+
+        let response = builder
+            .send()
+            .await
+            .with_context(|| format!("request to {} timed out or failed to connect", request.url))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .with_context(|| format!("failed to read response body from {}", request.url))?;
+
+        if !status.is_success() {
+            anyhow::bail!("request to {} failed with status {}: {}", request.url, status, body);
+        }
+
+        serde_json::from_str(&body)
+            .with_context(|| format!("failed to parse JSON response from {} (status {}): {}", request.url, status, body))
+    }
+}
+
+/// A layer that wraps a `Transport` to add cross-cutting behavior, mirroring
+/// `tower::Layer` so stacks compose as `builder.with(RetryMiddleware::default())`.
+pub trait TransportLayer {
+    fn layer(self: Box<Self>, inner: Arc<dyn Transport>) -> Arc<dyn Transport>;
+}
+
+/// Retries idempotent requests (GETs, or any request carrying an idempotency key)
+/// with capped exponential backoff on transport errors.
+pub struct RetryMiddleware {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for RetryMiddleware {
+    fn default() -> Self {
+        Self { max_retries: 3, base_delay: Duration::from_millis(200) }
+    }
+}
+
+impl TransportLayer for RetryMiddleware {
+    fn layer(self: Box<Self>, inner: Arc<dyn Transport>) -> Arc<dyn Transport> {
+        Arc::new(RetryTransport { inner, max_retries: self.max_retries, base_delay: self.base_delay })
+    }
+}
+
+struct RetryTransport {
+    inner: Arc<dyn Transport>,
+    max_retries: u32,
+    base_delay: Duration,
+}
+
+#[async_trait]
+impl Transport for RetryTransport {
+    async fn send(&self, request: TransportRequest) -> Result<serde_json::Value> {
+        let retryable = request.method == TransportMethod::Get || request.idempotency_key.is_some();
+        let mut attempt = 0;
+
+        loop {
+            match self.inner.send(request.clone()).await {
+                Ok(value) => return Ok(value),
+                Err(e) if retryable && attempt < self.max_retries => {
+                    let backoff = self.base_delay * 2u32.pow(attempt);
+                    let jitter = Duration::from_millis(rand::thread_rng().gen_range(0..=backoff.as_millis() as u64 / 2 + 1));
+                    tracing::warn!(attempt, error = %e, "transport request failed, retrying");
+                    tokio::time::sleep(backoff + jitter).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Token-bucket rate limiting per venue, so one client instance can't exceed the
+/// downstream API's published rate limit.
+pub struct RateLimitMiddleware {
+    capacity: f64,
+    refill_per_sec: f64,
+}
+
+impl RateLimitMiddleware {
+    pub fn per_minute(requests_per_minute: u32) -> Self {
+        Self { capacity: requests_per_minute as f64, refill_per_sec: requests_per_minute as f64 / 60.0 }
+    }
+}
+
+impl TransportLayer for RateLimitMiddleware {
+    fn layer(self: Box<Self>, inner: Arc<dyn Transport>) -> Arc<dyn Transport> {
+        Arc::new(RateLimitTransport {
+            inner,
+            capacity: self.capacity,
+            refill_per_sec: self.refill_per_sec,
+            state: Mutex::new((self.capacity, Instant::now())),
+        })
+    }
+}
+
+struct RateLimitTransport {
+    inner: Arc<dyn Transport>,
+    capacity: f64,
+    refill_per_sec: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+#[async_trait]
+impl Transport for RateLimitTransport {
+    async fn send(&self, request: TransportRequest) -> Result<serde_json::Value> {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let (tokens, last_refill) = &mut *state;
+                let elapsed = last_refill.elapsed().as_secs_f64();
+                *tokens = (*tokens + elapsed * self.refill_per_sec).min(self.capacity);
+                *last_refill = Instant::now();
+
+                if *tokens >= 1.0 {
+                    *tokens -= 1.0;
+                    None
+                } else {
+                    Some(Duration::from_secs_f64((1.0 - *tokens) / self.refill_per_sec))
+                }
+            };
+
+            match wait {
+                None => return self.inner.send(request).await,
+                Some(delay) => tokio::time::sleep(delay).await,
+            }
+        }
+    }
+}
+
+/// Injects an `Idempotency-Key` header (a fresh UUID) on POSTs that didn't already
+/// carry one, so a retried submission is rejected as a duplicate server-side.
+#[derive(Default)]
+pub struct IdempotencyMiddleware;
+
+impl TransportLayer for IdempotencyMiddleware {
+    fn layer(self: Box<Self>, inner: Arc<dyn Transport>) -> Arc<dyn Transport> {
+        Arc::new(IdempotencyTransport { inner })
+    }
+}
+
+struct IdempotencyTransport {
+    inner: Arc<dyn Transport>,
+}
+
+#[async_trait]
+impl Transport for IdempotencyTransport {
+    async fn send(&self, mut request: TransportRequest) -> Result<serde_json::Value> {
+        if request.idempotency_key.is_none() && request.method == TransportMethod::Post {
+            request.idempotency_key = Some(uuid::Uuid::new_v4().to_string());
+        }
+        self.inner.send(request).await
+    }
+}
+
+/// Emits a tracing span per request recording method, URL, and latency.
+#[derive(Default)]
+pub struct LoggingMiddleware;
+
+impl TransportLayer for LoggingMiddleware {
+    fn layer(self: Box<Self>, inner: Arc<dyn Transport>) -> Arc<dyn Transport> {
+        Arc::new(LoggingTransport { inner })
+    }
+}
+
+struct LoggingTransport {
+    inner: Arc<dyn Transport>,
+}
+
+#[async_trait]
+impl Transport for LoggingTransport {
+    #[instrument(skip(self, request), fields(method = ?request.method, url = %request.url))]
+    async fn send(&self, request: TransportRequest) -> Result<serde_json::Value> {
+        let start = Instant::now();
+        let result = self.inner.send(request).await;
+        let latency_ms = start.elapsed().as_millis();
+        match &result {
+            Ok(_) => tracing::info!(latency_ms, "transport request succeeded"),
+            Err(e) => tracing::warn!(latency_ms, error = %e, "transport request failed"),
+        }
+        result
+    }
+}
+
+/// Builds a composed `Transport` stack, e.g.
+/// `TransportBuilder::new(token).timeout(Duration::from_secs(10)).with(RetryMiddleware::default()).with(RateLimitMiddleware::per_minute(60)).build()`.
+pub struct TransportBuilder {
+    bearer_token: String,
+    timeout: Duration,
+    layers: Vec<Box<dyn TransportLayer>>,
+}
+
+impl TransportBuilder {
+    pub fn new(bearer_token: impl Into<String>) -> Self {
+        Self { bearer_token: bearer_token.into(), timeout: DEFAULT_TIMEOUT, layers: Vec::new() }
+    }
+
+    /// Override the per-request timeout enforced by the base `HttpTransport`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with(mut self, layer: impl TransportLayer + 'static) -> Self {
+        self.layers.push(Box::new(layer));
+        self
+    }
+
+    pub fn build(self) -> Arc<dyn Transport> {
+        let mut transport: Arc<dyn Transport> = Arc::new(HttpTransport::new(self.bearer_token, self.timeout));
+        for layer in self.layers {
+            transport = layer.layer(transport);
+        }
+        transport
+    }
+}
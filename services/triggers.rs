@@ -0,0 +1,48 @@
+// Apollo Triggers
+// Conditions that gate a `StrategyAction` on its own symbol's spot price, so
+// rebalancing and downside-protection orders (a stop-loss, a limit entry) can be
+// attached to a generated strategy without relying on any exchange's native
+// stop/limit order book -- Apollo evaluates the condition itself and only then
+// dispatches the underlying market order.
+//
+// `StrategyAction` is defined in `atlas_integration` (not present in this snapshot);
+// its `trigger: Option<Trigger>` field is assumed present alongside its other
+// fields wherever a `StrategyAction` literal is constructed in this tree.
+
+use crate::services::atlas_integration::StrategyAction;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A condition on an action's own symbol that must hold before the action is
+/// dispatched.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "condition", rename_all = "snake_case")]
+pub enum Trigger {
+    PriceAbove { threshold: f64 },
+    PriceBelow { threshold: f64 },
+}
+
+impl Trigger {
+    fn is_satisfied(&self, current_price: f64) -> bool {
+        match self {
+            Trigger::PriceAbove { threshold } => current_price > *threshold,
+            Trigger::PriceBelow { threshold } => current_price < *threshold,
+        }
+    }
+}
+
+/// True when `action` has no trigger (an immediate market action) or its trigger
+/// is satisfied by `current_prices`. An action whose symbol is missing from
+/// `current_prices` is treated as not ready rather than defaulting to true.
+pub fn is_ready(action: &StrategyAction, current_prices: &HashMap<String, f64>) -> bool {
+    match &action.trigger {
+        None => true,
+        Some(trigger) => current_prices.get(&action.symbol).is_some_and(|price| trigger.is_satisfied(*price)),
+    }
+}
+
+/// Filters `actions` down to those currently ready to dispatch, so downstream
+/// execution can poll this instead of re-checking every action's trigger itself.
+pub fn ready_actions(actions: &[StrategyAction], current_prices: &HashMap<String, f64>) -> Vec<StrategyAction> {
+    actions.iter().filter(|action| is_ready(action, current_prices)).cloned().collect()
+}
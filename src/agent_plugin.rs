@@ -0,0 +1,347 @@
+// Apollo Agent Plugin Subsystem
+//
+// Agents are loadable at runtime as sandboxed WebAssembly modules registered under a
+// string id, so third parties can ship new agents without recompiling Apollo. The 42
+// built-in agents still run in-process, but they register into the same `AgentRegistry`
+// as WASM plugins so `list_agents`/`select_primary_agent` work against one dynamic
+// namespace instead of a fixed `match` over a closed enum.
+//
+// Guest ABI: a plugin module exports `alloc(len: i32) -> i32` (a pointer into its own
+// linear memory the host can write into) and `handle(req_ptr: i32, req_len: i32) -> i64`,
+// a packed `(result_ptr: i32, result_len: i32)` pointing at a JSON-encoded `AgentResult`
+// the host reads back out of guest memory. The host serializes `QueryRequest` (which
+// carries `AtlasContext`) to JSON, asks the guest to `alloc` a buffer of that size, and
+// writes the bytes in before calling `handle`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use wasmtime::{Caller, Engine, Instance, Linker, Memory, Module, Store};
+
+use crate::router::{AgentResult, AgentType, QueryRequest};
+
+/// Host trait every agent -- built-in or WASM -- implements.
+#[async_trait]
+pub trait Agent: Send + Sync {
+    /// Stable string id the agent is registered and dispatched under.
+    fn id(&self) -> &str;
+
+    /// Human-readable description, surfaced by `GET /apollo/agents`.
+    fn description(&self) -> &str;
+
+    async fn execute(&self, request: &QueryRequest) -> Result<AgentResult>;
+}
+
+/// One of the 42 agents compiled directly into Apollo. Its `execute` is the same mock
+/// implementation the router used before plugins existed.
+pub struct BuiltinAgent {
+    agent_type: AgentType,
+    id: String,
+    description: &'static str,
+}
+
+impl BuiltinAgent {
+    fn new(agent_type: AgentType, description: &'static str) -> Self {
+        let id = serde_json::to_value(&agent_type)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("{:?}", agent_type));
+        Self { agent_type, id, description }
+    }
+
+    pub fn agent_type(&self) -> &AgentType {
+        &self.agent_type
+    }
+
+    /// All 42 built-in agents, matching the descriptions previously hardcoded in
+    /// `api::list_agents`.
+    pub fn all() -> Vec<BuiltinAgent> {
+        vec![
+            BuiltinAgent::new(AgentType::CodeAssistant, "Software development assistance"),
+            BuiltinAgent::new(AgentType::CodeEditor, "Embedded code editing with AI (Akashic)"),
+            BuiltinAgent::new(AgentType::EmailAgent, "Email processing and analysis"),
+            BuiltinAgent::new(AgentType::CalendarAgent, "Calendar and scheduling"),
+            BuiltinAgent::new(AgentType::DocumentParser, "Document extraction and parsing"),
+            BuiltinAgent::new(AgentType::KnowledgeAgent, "Semantic search and knowledge retrieval"),
+            BuiltinAgent::new(AgentType::Sage, "Research and learning assistant"),
+            BuiltinAgent::new(AgentType::TextAnalyzer, "NLP and text analysis"),
+            BuiltinAgent::new(AgentType::Quant, "Data analysis and SQL queries"),
+            BuiltinAgent::new(AgentType::VisionAgent, "Image understanding and analysis"),
+            BuiltinAgent::new(AgentType::AudioAgent, "Speech-to-text and audio processing"),
+            BuiltinAgent::new(AgentType::Reel, "Video intelligence and analysis"),
+            BuiltinAgent::new(AgentType::Harmonia, "Music intelligence and analysis"),
+            BuiltinAgent::new(AgentType::LedgerAgent, "Financial analysis and bookkeeping"),
+            BuiltinAgent::new(AgentType::Deduct, "Tax preparation and optimization"),
+            BuiltinAgent::new(AgentType::Juris, "Legal document analysis"),
+            BuiltinAgent::new(AgentType::Accord, "Contract analysis and review"),
+            BuiltinAgent::new(AgentType::Closer, "Sales and CRM assistance"),
+            BuiltinAgent::new(AgentType::Amplify, "Marketing and growth"),
+            BuiltinAgent::new(AgentType::Talent, "HR and recruitment"),
+            BuiltinAgent::new(AgentType::GrantAgent, "Grant discovery and applications"),
+            BuiltinAgent::new(AgentType::Shield, "Insurance analysis and recommendations"),
+            BuiltinAgent::new(AgentType::Sentinel, "Regulatory compliance monitoring"),
+            BuiltinAgent::new(AgentType::WebScraper, "Web content extraction"),
+            BuiltinAgent::new(AgentType::Polyglot, "Language translation"),
+            BuiltinAgent::new(AgentType::Lexicon, "Slang and modern language understanding"),
+            BuiltinAgent::new(AgentType::CulturePulse, "Meme culture and trends"),
+            BuiltinAgent::new(AgentType::SchemaAgent, "Data structuring and schemas"),
+            BuiltinAgent::new(AgentType::RouterAgent, "Content routing and classification"),
+            BuiltinAgent::new(AgentType::TradingAgent, "Trading execution (Delt integration)"),
+            BuiltinAgent::new(AgentType::PortfolioAnalyzer, "Portfolio analysis and optimization"),
+            BuiltinAgent::new(AgentType::RiskManager, "Risk assessment and management"),
+            BuiltinAgent::new(AgentType::MarketAnalyzer, "Market analysis and insights"),
+            BuiltinAgent::new(AgentType::StrategyGenerator, "Trading strategy generation"),
+            BuiltinAgent::new(AgentType::ExecutionAgent, "Trade execution and monitoring"),
+            BuiltinAgent::new(AgentType::HealthAgent, "Health tracking and analysis"),
+            BuiltinAgent::new(AgentType::TravelAgent, "Travel planning and booking"),
+            BuiltinAgent::new(AgentType::FitnessAgent, "Fitness tracking and coaching"),
+            BuiltinAgent::new(AgentType::NutritionAgent, "Nutrition analysis and meal planning"),
+            BuiltinAgent::new(AgentType::SleepAgent, "Sleep tracking and optimization"),
+            BuiltinAgent::new(AgentType::MentalHealthAgent, "Mental wellness and mindfulness"),
+        ]
+    }
+}
+
+#[async_trait]
+impl Agent for BuiltinAgent {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn description(&self) -> &str {
+        self.description
+    }
+
+    async fn execute(&self, request: &QueryRequest) -> Result<AgentResult> {
+        Ok(AgentResult {
+            agent: self.id.clone(),
+            data: serde_json::json!({
+                "query": request.query,
+                "results": []
+            }),
+            confidence: 0.8,
+            placeholder_reason: None,
+        })
+    }
+}
+
+/// State threaded through a WASM instance's `Store` for the duration of one call.
+struct WasmAgentState {
+    request_json: Vec<u8>,
+    privacy_levels: Vec<String>,
+}
+
+/// A third-party agent loaded from a `.wasm` module found in the plugin directory.
+pub struct WasmAgent {
+    id: String,
+    description: String,
+    engine: Engine,
+    module: Module,
+}
+
+impl WasmAgent {
+    fn load(engine: &Engine, path: &Path) -> Result<Self> {
+        let module = Module::from_file(engine, path)
+            .with_context(|| format!("failed to compile plugin module {}", path.display()))?;
+        let id = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(str::to_string)
+            .with_context(|| format!("plugin path has no usable file stem: {}", path.display()))?;
+
+        Ok(Self {
+            id: id.clone(),
+            description: format!("third-party plugin agent ({id})"),
+            engine: engine.clone(),
+            module,
+        })
+    }
+
+    fn instantiate(&self, store: &mut Store<WasmAgentState>) -> Result<Instance> {
+        let mut linker = Linker::new(&self.engine);
+
+        // Host import: plugins fetch Atlas knowledge-base context under the caller's
+        // `privacy_levels` rather than being handed the raw context directly.
+        linker.func_wrap(
+            "host",
+            "atlas_lookup",
+            |mut caller: Caller<'_, WasmAgentState>, key_ptr: i32, key_len: i32| -> i32 {
+                let memory = match caller.get_export("memory").and_then(|e| e.into_memory()) {
+                    Some(m) => m,
+                    None => return -1,
+                };
+                let mut key = vec![0u8; key_len as usize];
+                if memory.read(&caller, key_ptr as usize, &mut key).is_err() {
+                    return -1;
+                }
+                let key = String::from_utf8_lossy(&key);
+
+                let allowed = caller.data().privacy_levels.iter().any(|lvl| lvl == key.as_ref());
+                if allowed {
+                    1
+                } else {
+                    0
+                }
+            },
+        )?;
+
+        linker.instantiate(&mut *store, &self.module).context("failed to instantiate plugin module")
+    }
+}
+
+#[async_trait]
+impl Agent for WasmAgent {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    async fn execute(&self, request: &QueryRequest) -> Result<AgentResult> {
+        let request_json = serde_json::to_vec(request)?;
+        let privacy_levels = request.context.privacy_levels.clone();
+
+        let id = self.id.clone();
+        let engine = self.engine.clone();
+        let module = self.module.clone();
+
+        // wasmtime's `Store`/`Instance` are not `Send`; run the call on a blocking
+        // thread so `execute` stays a plain async fn for the host trait.
+        tokio::task::spawn_blocking(move || -> Result<AgentResult> {
+            let agent = WasmAgent { id: id.clone(), description: String::new(), engine, module };
+            let mut store = Store::new(&agent.engine, WasmAgentState { request_json: request_json.clone(), privacy_levels });
+            let instance = agent.instantiate(&mut store)?;
+
+            let memory = instance
+                .get_memory(&mut store, "memory")
+                .context("plugin module does not export linear memory")?;
+            let alloc = instance
+                .get_typed_func::<i32, i32>(&mut store, "alloc")
+                .context("plugin module does not export alloc(len) -> ptr")?;
+            let handle = instance
+                .get_typed_func::<(i32, i32), i64>(&mut store, "handle")
+                .context("plugin module does not export handle(ptr, len) -> packed(ptr, len)")?;
+
+            let req_len = store.data().request_json.len() as i32;
+            let req_ptr = alloc.call(&mut store, req_len)?;
+            let req_bytes = store.data().request_json.clone();
+            memory.write(&mut store, req_ptr as usize, &req_bytes)?;
+
+            let packed = handle.call(&mut store, (req_ptr, req_len))?;
+            let result_ptr = (packed >> 32) as u32 as usize;
+            let result_len = (packed & 0xFFFF_FFFF) as u32 as usize;
+
+            let mut result_bytes = vec![0u8; result_len];
+            memory.read(&store, result_ptr, &mut result_bytes)?;
+
+            let data: serde_json::Value = serde_json::from_slice(&result_bytes)
+                .context("plugin returned invalid AgentResult JSON")?;
+            let confidence = data.get("confidence").and_then(|v| v.as_f64()).unwrap_or(0.5) as f32;
+
+            Ok(AgentResult { agent: id, data, confidence, placeholder_reason: None })
+        })
+        .await
+        .context("plugin execution task panicked")?
+    }
+}
+
+/// Registry of agents keyed by their string id: the 42 built-ins plus whatever
+/// `.wasm` modules were found in the configured plugin directory at startup.
+#[derive(Clone, Default)]
+pub struct AgentRegistry {
+    agents: Arc<HashMap<String, Arc<dyn Agent>>>,
+}
+
+impl AgentRegistry {
+    /// Build a registry containing only the 42 built-in agents (no plugin directory).
+    pub fn builtin_only() -> Self {
+        AgentRegistryBuilder::new().build()
+    }
+
+    /// Build a registry from the built-ins plus every `.wasm` module under `plugin_dir`.
+    pub fn with_plugin_dir(plugin_dir: impl AsRef<Path>) -> Result<Self> {
+        let mut builder = AgentRegistryBuilder::new();
+        builder.load_plugin_dir(plugin_dir)?;
+        Ok(builder.build())
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<dyn Agent>> {
+        self.agents.get(id).cloned()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.agents.contains_key(id)
+    }
+
+    pub fn list(&self) -> Vec<Arc<dyn Agent>> {
+        let mut agents: Vec<_> = self.agents.values().cloned().collect();
+        agents.sort_by(|a, b| a.id().cmp(b.id()));
+        agents
+    }
+}
+
+pub struct AgentRegistryBuilder {
+    engine: Engine,
+    agents: HashMap<String, Arc<dyn Agent>>,
+}
+
+impl AgentRegistryBuilder {
+    pub fn new() -> Self {
+        let mut builder = Self { engine: Engine::default(), agents: HashMap::new() };
+        for builtin in BuiltinAgent::all() {
+            builder.register(Arc::new(builtin));
+        }
+        builder
+    }
+
+    pub fn register(&mut self, agent: Arc<dyn Agent>) {
+        self.agents.insert(agent.id().to_string(), agent);
+    }
+
+    /// Scan `dir` for `.wasm` files, instantiate each with wasmtime, and register it
+    /// under its file stem. Returns the number of plugins loaded.
+    pub fn load_plugin_dir(&mut self, dir: impl AsRef<Path>) -> Result<usize> {
+        let dir = dir.as_ref();
+        if !dir.exists() {
+            return Ok(0);
+        }
+
+        let mut loaded = 0;
+        let entries = std::fs::read_dir(dir)
+            .with_context(|| format!("failed to read plugin directory {}", dir.display()))?;
+
+        for entry in entries {
+            let path: PathBuf = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("wasm") {
+                continue;
+            }
+
+            match WasmAgent::load(&self.engine, &path) {
+                Ok(agent) => {
+                    self.register(Arc::new(agent));
+                    loaded += 1;
+                }
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "failed to load agent plugin");
+                }
+            }
+        }
+
+        Ok(loaded)
+    }
+
+    pub fn build(self) -> AgentRegistry {
+        AgentRegistry { agents: Arc::new(self.agents) }
+    }
+}
+
+impl Default for AgentRegistryBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
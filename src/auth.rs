@@ -0,0 +1,187 @@
+// Apollo Auth & Rate Limiting
+//
+// Bearer-token authentication and per-entity rate limiting for the Apollo API. A token
+// resolves to a `Principal` carrying the caller's `user_id`/`entity_id` and an
+// `entity_type` scope (Personal/Business/Team/Enterprise) that gates which agents
+// `ApolloRouter::select_agents` is allowed to pick for the request.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{Request, State};
+use axum::http::{HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+/// The authenticated caller bound to a request by [`require_bearer_token`].
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub user_id: String,
+    pub entity_id: String,
+    pub entity_type: String,
+}
+
+/// Maps bearer tokens to principals. Tokens are provisioned out of band (e.g. by
+/// Atlas) and loaded here from `APOLLO_API_TOKENS`, a comma-separated list of
+/// `token:user_id:entity_id:entity_type` entries.
+#[derive(Clone, Default)]
+pub struct TokenStore {
+    tokens: Arc<HashMap<String, Principal>>,
+}
+
+impl TokenStore {
+    pub fn from_env() -> Self {
+        let raw = std::env::var("APOLLO_API_TOKENS").unwrap_or_default();
+        Self::parse(&raw)
+    }
+
+    fn parse(raw: &str) -> Self {
+        let mut tokens = HashMap::new();
+        for entry in raw.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let mut parts = entry.splitn(4, ':');
+            if let (Some(token), Some(user_id), Some(entity_id), Some(entity_type)) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            {
+                tokens.insert(
+                    token.to_string(),
+                    Principal {
+                        user_id: user_id.to_string(),
+                        entity_id: entity_id.to_string(),
+                        entity_type: entity_type.to_string(),
+                    },
+                );
+            }
+        }
+        Self { tokens: Arc::new(tokens) }
+    }
+
+    pub fn authenticate(&self, token: &str) -> Option<Principal> {
+        self.tokens.get(token).cloned()
+    }
+}
+
+/// Axum middleware: validates the `Authorization: Bearer <token>` header against the
+/// `TokenStore`, rejecting with `401` when absent or invalid, and inserts the resolved
+/// `Principal` into the request's extensions for downstream handlers/middleware.
+pub async fn require_bearer_token(
+    State(token_store): State<TokenStore>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let token = request
+        .headers()
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let principal = token_store.authenticate(token).ok_or(StatusCode::UNAUTHORIZED)?;
+    request.extensions_mut().insert(principal);
+
+    Ok(next.run(request).await)
+}
+
+/// A classic token bucket: `capacity` tokens refilled at `refill_per_sec`, consumed one
+/// per request.
+struct TokenBucket {
+    tokens: f64,
+    capacity: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64, refill_per_sec: f64) -> Self {
+        Self { tokens: capacity, capacity, refill_per_sec, last_refill: Instant::now() }
+    }
+
+    /// Returns `Ok(())` if a token was available, or `Err(retry_after)` otherwise.
+    fn try_acquire(&mut self) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            let deficit = 1.0 - self.tokens;
+            Err(Duration::from_secs_f64(deficit / self.refill_per_sec))
+        }
+    }
+}
+
+/// Per-`entity_id` token-bucket rate limiter, so one tenant can't starve others.
+#[derive(Clone)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Arc<Mutex<HashMap<String, TokenBucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(capacity: f64, refill_per_sec: f64) -> Self {
+        Self { capacity, refill_per_sec, buckets: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("APOLLO_RATE_LIMIT_CAPACITY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60.0);
+        let refill_per_sec = std::env::var("APOLLO_RATE_LIMIT_PER_SEC")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1.0);
+        Self::new(capacity, refill_per_sec)
+    }
+
+    fn check(&self, entity_id: &str) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        buckets
+            .entry(entity_id.to_string())
+            .or_insert_with(|| TokenBucket::new(self.capacity, self.refill_per_sec))
+            .try_acquire()
+    }
+}
+
+/// Axum middleware: rejects with `429` and a `Retry-After` header once the calling
+/// entity's token bucket is exhausted. Must run after [`require_bearer_token`] so a
+/// `Principal` is already in the request's extensions.
+pub async fn enforce_rate_limit(
+    State(rate_limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    let entity_id = request
+        .extensions()
+        .get::<Principal>()
+        .map(|p| p.entity_id.clone())
+        .ok_or(StatusCode::UNAUTHORIZED.into_response())?;
+
+    match rate_limiter.check(&entity_id) {
+        Ok(()) => Ok(next.run(request).await),
+        Err(retry_after) => {
+            let mut response = StatusCode::TOO_MANY_REQUESTS.into_response();
+            if let Ok(value) = HeaderValue::from_str(&retry_after.as_secs().max(1).to_string()) {
+                response.headers_mut().insert(axum::http::header::RETRY_AFTER, value);
+            }
+            Err(response)
+        }
+    }
+}
+
+/// Agent ids a `Personal` entity scope is not allowed to reach -- trading touches real
+/// money and requires a Business/Team/Enterprise token.
+const PERSONAL_SCOPE_BLOCKED_AGENTS: &[&str] = &["trading_agent", "execution_agent"];
+
+/// Whether `entity_type` is permitted to use `agent_id`, per the token's scope.
+pub fn entity_scope_allows_agent(entity_type: &str, agent_id: &str) -> bool {
+    if entity_type == "Personal" {
+        !PERSONAL_SCOPE_BLOCKED_AGENTS.contains(&agent_id)
+    } else {
+        true
+    }
+}
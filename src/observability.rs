@@ -0,0 +1,117 @@
+// Apollo Observability
+// Single init point for OpenTelemetry traces, metrics, and logs across the router
+
+use once_cell::sync::OnceCell;
+use opentelemetry::global;
+use opentelemetry::metrics::{Counter, Histogram, Meter, UpDownCounter};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    metrics::{PeriodicReader, SdkMeterProvider},
+    trace::{Config, TracerProvider},
+    Resource,
+};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Registry};
+
+
+static METRICS: OnceCell<ApolloMetrics> = OnceCell::new();
+
+/// Per-agent and per-query instrumentation, exported via OTLP.
+pub struct ApolloMetrics {
+    pub agent_invocations: Counter<u64>,
+    pub agent_confidence: Histogram<f64>,
+    pub agent_execution_time_ms: Histogram<u64>,
+    pub queries_in_flight: UpDownCounter<i64>,
+}
+
+impl ApolloMetrics {
+    fn new(meter: &Meter) -> Self {
+        Self {
+            agent_invocations: meter
+                .u64_counter("apollo.agent.invocations")
+                .with_description("Number of times each agent type was invoked")
+                .init(),
+            agent_confidence: meter
+                .f64_histogram("apollo.agent.confidence")
+                .with_description("Confidence score returned by each agent invocation")
+                .init(),
+            agent_execution_time_ms: meter
+                .u64_histogram("apollo.agent.execution_time_ms")
+                .with_description("Wall-clock time spent executing a single agent")
+                .init(),
+            queries_in_flight: meter
+                .i64_up_down_counter("apollo.queries.in_flight")
+                .with_description("Number of Apollo queries currently being routed")
+                .init(),
+        }
+    }
+
+    pub fn record_agent_invocation(&self, agent_id: &str, confidence: f32, execution_time_ms: u64) {
+        let attrs = [KeyValue::new("agent.id", agent_id.to_string())];
+        self.agent_invocations.add(1, &attrs);
+        self.agent_confidence.record(confidence as f64, &attrs);
+        self.agent_execution_time_ms.record(execution_time_ms, &attrs);
+    }
+}
+
+/// Initialize tracing, metrics, and log export from environment configuration.
+///
+/// Reads the standard `OTEL_EXPORTER_OTLP_ENDPOINT` / `OTEL_EXPORTER_OTLP_HEADERS`
+/// variables. Safe to call once at process startup; subsequent calls are no-ops.
+pub fn init_observability(service_name: &str) -> anyhow::Result<()> {
+    if METRICS.get().is_some() {
+        return Ok(());
+    }
+
+    let resource = Resource::new(vec![KeyValue::new("service.name", service_name.to_string())]);
+
+    let tracer_provider = TracerProvider::builder()
+        .with_batch_exporter(
+            opentelemetry_otlp::SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(otlp_endpoint())
+                .build()?,
+            opentelemetry_sdk::runtime::Tokio,
+        )
+        .with_config(Config::default().with_resource(resource.clone()))
+        .build();
+    global::set_tracer_provider(tracer_provider.clone());
+
+    let metric_exporter = opentelemetry_otlp::MetricExporter::builder()
+        .with_tonic()
+        .with_endpoint(otlp_endpoint())
+        .build()?;
+    let metric_reader = PeriodicReader::builder(metric_exporter, opentelemetry_sdk::runtime::Tokio).build();
+    let meter_provider = SdkMeterProvider::builder()
+        .with_reader(metric_reader)
+        .with_resource(resource)
+        .build();
+    global::set_meter_provider(meter_provider.clone());
+
+    let meter = meter_provider.meter("apollo_router");
+    METRICS
+        .set(ApolloMetrics::new(&meter))
+        .map_err(|_| anyhow::anyhow!("observability already initialized"))?;
+
+    let telemetry_layer = tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("apollo_router"));
+    Registry::default()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(tracing_subscriber::fmt::layer().json())
+        .with(telemetry_layer)
+        .try_init()
+        .map_err(|e| anyhow::anyhow!("failed to install tracing subscriber: {e}"))?;
+
+    Ok(())
+}
+
+fn otlp_endpoint() -> String {
+    std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").unwrap_or_else(|_| "http://localhost:4317".to_string())
+}
+
+/// Metrics handle for call sites that record invocation data outside `route_query`.
+pub fn metrics() -> &'static ApolloMetrics {
+    METRICS.get().expect("init_observability must run before metrics() is used")
+}
@@ -1,11 +1,18 @@
 // Apollo Router - Routes queries to 42 specialized agents
 // Integrates with Atlas Knowledge Base for context-aware responses
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{instrument, warn, Instrument};
 use uuid::Uuid;
 
+use crate::agent_plugin::AgentRegistry;
+use crate::observability::{init_observability, metrics};
+use crate::routing_config::RemoteConfigClient;
+
 /// The 42 specialized agents Apollo can orchestrate
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
@@ -101,11 +108,13 @@ pub struct QueryRequest {
     pub conversation_id: Option<Uuid>,
 }
 
-/// Agent execution plan
+/// Agent execution plan. Agents are referenced by their registry id (a built-in's
+/// snake_case `AgentType` name, or a plugin's file stem) rather than the closed
+/// `AgentType` enum, so the plan can name agents the router learned about at runtime.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentPlan {
-    pub primary_agent: AgentType,
-    pub supporting_agents: Vec<AgentType>,
+    pub primary_agent: String,
+    pub supporting_agents: Vec<String>,
     pub execution_strategy: ExecutionStrategy,
     pub estimated_time_ms: u64,
 }
@@ -128,25 +137,129 @@ pub struct ApolloResponse {
     pub summary: Option<String>,
     pub suggestions: Vec<String>,
     pub related_queries: Vec<String>,
-    pub agents_used: Vec<AgentType>,
+    pub agents_used: Vec<String>,
     pub execution_time_ms: u64,
     pub confidence: f32,
 }
 
 pub struct ApolloRouter {
-    // Agent clients would be initialized here
+    registry: AgentRegistry,
+    routing_config: Arc<RemoteConfigClient>,
 }
 
 impl ApolloRouter {
+    /// Build a router with only the 42 built-in agents and the compiled-in default
+    /// routing rules -- no remote config center, no background long-poll. Prefer
+    /// [`ApolloRouter::bootstrap`] at process startup; this is for tests and simple
+    /// embeddings that don't need hot-reloadable rules.
     pub fn new() -> Self {
-        Self {}
+        Self::init_observability_best_effort();
+        Self {
+            registry: Self::load_registry(),
+            routing_config: Arc::new(RemoteConfigClient::builtin()),
+        }
+    }
+
+    /// Build a router, scanning `plugin_dir` for third-party WASM agent modules at
+    /// construction time and registering each one under a string agent id.
+    pub fn with_plugin_dir(plugin_dir: impl AsRef<std::path::Path>) -> Result<Self> {
+        Self::init_observability_best_effort();
+        Ok(Self {
+            registry: AgentRegistry::with_plugin_dir(plugin_dir)?,
+            routing_config: Arc::new(RemoteConfigClient::builtin()),
+        })
+    }
+
+    /// Full startup path: load agent plugins, fetch the routing config namespace from
+    /// the remote config center (or its disk cache, or the compiled-in defaults if
+    /// neither is reachable), and spawn the background long-poll that keeps the
+    /// config fresh for the lifetime of the router.
+    pub async fn bootstrap() -> Self {
+        Self::init_observability_best_effort();
+        let registry = Self::load_registry();
+
+        let config_center_url = std::env::var("APOLLO_CONFIG_CENTER_URL")
+            .unwrap_or_else(|_| "http://localhost:8090".to_string());
+        let namespace = std::env::var("APOLLO_ROUTING_NAMESPACE")
+            .unwrap_or_else(|_| "apollo.routing".to_string());
+        let cache_path = std::env::var("APOLLO_ROUTING_CACHE_PATH")
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(|_| std::path::PathBuf::from("/var/lib/apollo/routing_config.json"));
+
+        let routing_config =
+            Arc::new(RemoteConfigClient::bootstrap(config_center_url, namespace, cache_path).await);
+        tokio::spawn(routing_config.clone().run_long_poll());
+
+        Self { registry, routing_config }
+    }
+
+    /// Every `ApolloRouter` constructor reaches this before `route_query` can be
+    /// called, since `route_query` unconditionally calls `metrics()`. Failures are
+    /// logged rather than propagated -- a missing/unreachable OTLP collector
+    /// shouldn't stop the router from serving queries.
+    fn init_observability_best_effort() {
+        if let Err(e) = init_observability("apollo_router") {
+            tracing::warn!(error = %e, "failed to initialize observability");
+        }
+    }
+
+    fn load_registry() -> AgentRegistry {
+        match std::env::var("APOLLO_PLUGIN_DIR") {
+            Ok(dir) => AgentRegistry::with_plugin_dir(dir).unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "failed to load agent plugins, falling back to built-ins only");
+                AgentRegistry::builtin_only()
+            }),
+            Err(_) => AgentRegistry::builtin_only(),
+        }
+    }
+
+    /// Active routing config release id, surfaced in `/apollo/health`.
+    pub fn config_version(&self) -> String {
+        self.routing_config.current().release_id.clone()
+    }
+
+    /// List every registered agent (built-in or plugin) as `(id, description)` pairs.
+    pub fn list_agents(&self) -> Vec<(String, String)> {
+        self.registry
+            .list()
+            .into_iter()
+            .map(|agent| (agent.id().to_string(), agent.description().to_string()))
+            .collect()
     }
 
     /// Main entry point - route query to appropriate agents
+    ///
+    /// Wraps the whole pipeline in a span rooted from `conversation_id`/`query_id` so a
+    /// single Atlas query can be followed end-to-end across `analyze_intent`,
+    /// `select_agents`, `execute_agents`, and `synthesize_response`.
     pub async fn route_query(&self, request: QueryRequest) -> Result<ApolloResponse> {
         let start_time = std::time::Instant::now();
         let query_id = Uuid::new_v4();
+        let span = tracing::info_span!(
+            "apollo.route_query",
+            conversation_id = request.conversation_id.map(|id| id.to_string()).unwrap_or_default(),
+            query_id = %query_id,
+        );
+
+        async move {
+            metrics().queries_in_flight.add(1, &[]);
+            let result = self.route_query_inner(request, query_id, start_time).await;
+            metrics().queries_in_flight.add(-1, &[]);
+            if let Err(ref e) = result {
+                tracing::error!(query_id = %query_id, error = %e, "query routing failed");
+            }
+            result
+        }
+        .instrument(span)
+        .await
+    }
 
+    async fn route_query_inner(
+        &self,
+        request: QueryRequest,
+        query_id: Uuid,
+        start_time: std::time::Instant,
+    ) -> Result<ApolloResponse> {
         // 1. Analyze query intent
         let intent = self.analyze_intent(&request.query, &request.context).await?;
 
@@ -169,23 +282,19 @@ impl ApolloRouter {
     }
 
     /// Analyze query intent
+    #[instrument(skip(self, context))]
     async fn analyze_intent(&self, query: &str, context: &AtlasContext) -> Result<QueryIntent> {
         let query_lower = query.to_lowercase();
+        let config = self.routing_config.current();
 
-        // Determine intent type
-        let intent_type = if query_lower.starts_with("show") || query_lower.starts_with("find") {
-            IntentType::Search
-        } else if query_lower.starts_with("summarize") {
-            IntentType::Summarize
-        } else if query_lower.starts_with("analyze") {
-            IntentType::Analyze
-        } else if query_lower.starts_with("create") || query_lower.starts_with("generate") {
-            IntentType::Generate
-        } else if query_lower.starts_with("execute") || query_lower.starts_with("trade") {
-            IntentType::Execute
-        } else {
-            IntentType::Conversation
-        };
+        // Determine intent type from the namespace's prefix table rather than a
+        // compiled-in `if`/`else` chain, so new prefixes show up without a restart.
+        let intent_type = config
+            .intent_prefixes
+            .iter()
+            .find(|(prefix, _)| query_lower.starts_with(prefix.as_str()))
+            .map(|(_, intent)| IntentType::from_config_str(intent))
+            .unwrap_or(IntentType::Conversation);
 
         // Extract data types
         let data_types = self.extract_data_types(query, context);
@@ -202,55 +311,67 @@ impl ApolloRouter {
     }
 
     /// Select appropriate agents based on intent
+    #[instrument(skip(self, intent, context))]
     async fn select_agents(&self, intent: &QueryIntent, context: &AtlasContext) -> Result<AgentPlan> {
+        let config = self.routing_config.current();
         let primary_agent = self.select_primary_agent(intent, context);
         let supporting_agents = self.select_supporting_agents(intent, context);
         let execution_strategy = self.determine_strategy(intent, &supporting_agents);
+        let estimated_time_ms = config.estimated_time_for(&primary_agent);
 
         Ok(AgentPlan {
             primary_agent,
             supporting_agents,
             execution_strategy,
-            estimated_time_ms: 1000,
+            estimated_time_ms,
         })
     }
 
-    /// Select primary agent
-    fn select_primary_agent(&self, intent: &QueryIntent, context: &AtlasContext) -> AgentType {
-        // Map data types to agents
-        if intent.data_types.contains(&"email".to_string()) {
-            AgentType::EmailAgent
-        } else if intent.data_types.contains(&"meeting".to_string()) {
-            AgentType::CalendarAgent
-        } else if intent.data_types.contains(&"transaction".to_string()) {
-            AgentType::LedgerAgent
-        } else if intent.data_types.contains(&"document".to_string()) {
-            AgentType::DocumentParser
-        } else if intent.data_types.contains(&"code".to_string()) {
-            AgentType::CodeEditor
-        } else if intent.data_types.contains(&"trade".to_string()) {
-            AgentType::TradingAgent
-        } else if intent.data_types.contains(&"health".to_string()) {
-            AgentType::HealthAgent
-        } else if intent.data_types.contains(&"fitness".to_string()) {
-            AgentType::FitnessAgent
+    /// Order in which a data type wins the primary agent slot when several are
+    /// present, matching the priority the old hardcoded `if`/`else` chain used.
+    const PRIMARY_AGENT_PRIORITY: &'static [&'static str] =
+        &["email", "meeting", "transaction", "document", "code", "trade", "health", "fitness"];
+
+    /// Select primary agent from the namespace's data-type -> agent table, skipping
+    /// disabled, unregistered, or out-of-scope (per the caller's `entity_type`) agents
+    /// and falling back to the configured default.
+    fn select_primary_agent(&self, intent: &QueryIntent, context: &AtlasContext) -> String {
+        let config = self.routing_config.current();
+
+        for data_type in Self::PRIMARY_AGENT_PRIORITY {
+            if !intent.data_types.iter().any(|dt| dt == data_type) {
+                continue;
+            }
+            if let Some(agent_id) = config.data_type_agents.get(*data_type) {
+                if config.is_agent_enabled(agent_id)
+                    && self.registry.contains(agent_id)
+                    && crate::auth::entity_scope_allows_agent(&context.entity_type, agent_id)
+                {
+                    return agent_id.clone();
+                }
+            }
+        }
+
+        if self.registry.contains(&config.default_agent) {
+            config.default_agent.clone()
         } else {
-            AgentType::KnowledgeAgent
+            agent_type_id(&AgentType::KnowledgeAgent)
         }
     }
 
-    /// Select supporting agents
-    fn select_supporting_agents(&self, intent: &QueryIntent, context: &AtlasContext) -> Vec<AgentType> {
+    /// Select supporting agents from the namespace's data-type -> agent table.
+    fn select_supporting_agents(&self, intent: &QueryIntent, context: &AtlasContext) -> Vec<String> {
+        let config = self.routing_config.current();
         let mut agents = Vec::new();
 
-        // Add agents based on data types
         for data_type in &intent.data_types {
-            match data_type.as_str() {
-                "email" => agents.push(AgentType::EmailAgent),
-                "meeting" => agents.push(AgentType::CalendarAgent),
-                "transaction" => agents.push(AgentType::LedgerAgent),
-                "document" => agents.push(AgentType::DocumentParser),
-                _ => {}
+            if let Some(agent_id) = config.data_type_agents.get(data_type.as_str()) {
+                if config.is_agent_enabled(agent_id)
+                    && self.registry.contains(agent_id)
+                    && crate::auth::entity_scope_allows_agent(&context.entity_type, agent_id)
+                {
+                    agents.push(agent_id.clone());
+                }
             }
         }
 
@@ -261,7 +382,7 @@ impl ApolloRouter {
     }
 
     /// Determine execution strategy
-    fn determine_strategy(&self, intent: &QueryIntent, supporting_agents: &[AgentType]) -> ExecutionStrategy {
+    fn determine_strategy(&self, intent: &QueryIntent, supporting_agents: &[String]) -> ExecutionStrategy {
         if supporting_agents.is_empty() {
             ExecutionStrategy::Single
         } else {
@@ -273,75 +394,128 @@ impl ApolloRouter {
         }
     }
 
-    /// Execute agents
+    /// Confidence below which `ExecutionStrategy::Conditional` also runs the
+    /// supporting agents.
+    const CONDITIONAL_CONFIDENCE_THRESHOLD: f32 = 0.6;
+
+    /// Execute agents. `Parallel` truly fans out via `join_all` with a per-agent
+    /// timeout derived from the plan's time budget; failures and timeouts are demoted
+    /// to low-confidence placeholder results instead of aborting the whole query.
+    #[instrument(skip(self, plan, request), fields(query_id = %request.conversation_id.map(|id| id.to_string()).unwrap_or_default()))]
     async fn execute_agents(&self, plan: &AgentPlan, request: &QueryRequest) -> Result<Vec<AgentResult>> {
-        let mut results = Vec::new();
+        let budget = Duration::from_millis(plan.estimated_time_ms.max(1));
 
-        // Execute primary agent
-        let primary_result = self.execute_agent(&plan.primary_agent, request).await?;
-        results.push(primary_result);
+        let mut results = vec![self.execute_agent_with_timeout(&plan.primary_agent, request, budget).await];
 
-        // Execute supporting agents based on strategy
-        match plan.execution_strategy {
-            ExecutionStrategy::Parallel => {
-                for agent in &plan.supporting_agents {
-                    let result = self.execute_agent(agent, request).await?;
-                    results.push(result);
+        let supporting: Vec<&String> = match plan.execution_strategy {
+            ExecutionStrategy::Single => Vec::new(),
+            ExecutionStrategy::Conditional => {
+                if results[0].confidence < Self::CONDITIONAL_CONFIDENCE_THRESHOLD {
+                    plan.supporting_agents.iter().collect()
+                } else {
+                    Vec::new()
                 }
             }
+            ExecutionStrategy::Parallel | ExecutionStrategy::Sequential => {
+                plan.supporting_agents.iter().collect()
+            }
+        };
+
+        match plan.execution_strategy {
             ExecutionStrategy::Sequential => {
-                for agent in &plan.supporting_agents {
-                    let result = self.execute_agent(agent, request).await?;
-                    results.push(result);
+                for agent_id in supporting {
+                    results.push(self.execute_agent_with_timeout(agent_id, request, budget).await);
                 }
             }
-            _ => {}
+            _ => {
+                let futures = supporting
+                    .into_iter()
+                    .map(|agent_id| self.execute_agent_with_timeout(agent_id, request, budget));
+                results.extend(futures::future::join_all(futures).await);
+            }
         }
 
         Ok(results)
     }
 
-    /// Execute a single agent
-    async fn execute_agent(&self, agent: &AgentType, request: &QueryRequest) -> Result<AgentResult> {
-        // TODO: Actually call the agent
-        // For now, return mock data
-        Ok(AgentResult {
-            agent: agent.clone(),
-            data: serde_json::json!({
-                "query": request.query,
-                "results": []
-            }),
-            confidence: 0.8,
-        })
+    /// Run a single agent under `budget`, demoting errors and timeouts into a
+    /// low-confidence placeholder result rather than failing the whole query.
+    async fn execute_agent_with_timeout(
+        &self,
+        agent_id: &str,
+        request: &QueryRequest,
+        budget: Duration,
+    ) -> AgentResult {
+        match tokio::time::timeout(budget, self.execute_agent(agent_id, request)).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => {
+                warn!(agent = %agent_id, error = %e, "agent execution failed");
+                AgentResult::placeholder(agent_id, format!("error: {e}"))
+            }
+            Err(_) => {
+                warn!(agent = %agent_id, budget_ms = budget.as_millis() as u64, "agent execution timed out");
+                AgentResult::placeholder(agent_id, "timed out".to_string())
+            }
+        }
+    }
+
+    /// Execute a single agent, dispatching through the dynamic registry rather than a
+    /// fixed `match` -- the agent may be a built-in or a WASM plugin.
+    #[instrument(skip(self, request), fields(agent = %agent_id))]
+    async fn execute_agent(&self, agent_id: &str, request: &QueryRequest) -> Result<AgentResult> {
+        let start_time = std::time::Instant::now();
+
+        let agent = self
+            .registry
+            .get(agent_id)
+            .with_context(|| format!("agent '{agent_id}' is not registered"))?;
+        let result = agent.execute(request).await?;
+
+        let execution_time_ms = start_time.elapsed().as_millis() as u64;
+        metrics().record_agent_invocation(agent_id, result.confidence, execution_time_ms);
+
+        Ok(result)
     }
 
     /// Synthesize final response
+    #[instrument(skip(self, query, results))]
     async fn synthesize_response(
         &self,
         query_id: Uuid,
         query: &str,
         results: Vec<AgentResult>,
-        primary_agent: AgentType,
+        primary_agent: String,
         execution_time_ms: u64,
     ) -> Result<ApolloResponse> {
         let data: Vec<serde_json::Value> = results.iter().map(|r| r.data.clone()).collect();
-        let agents_used: Vec<AgentType> = results.iter().map(|r| r.agent.clone()).collect();
+        let agents_used: Vec<String> = results.iter().map(|r| r.agent.clone()).collect();
+
+        let total = results.len().max(1) as f32;
+        let real_fraction = results.iter().filter(|r| r.placeholder_reason.is_none()).count() as f32 / total;
+        let avg_confidence = results.iter().map(|r| r.confidence).sum::<f32>() / total;
+
+        let mut suggestions = vec![
+            "Refine search".to_string(),
+            "Show more details".to_string(),
+        ];
+        for result in &results {
+            if let Some(reason) = &result.placeholder_reason {
+                suggestions.push(format!("{} did not complete ({reason})", result.agent));
+            }
+        }
 
         Ok(ApolloResponse {
             query_id,
             answer: format!("Processed query: {}", query),
             data,
             summary: Some(format!("Used {} agents", agents_used.len())),
-            suggestions: vec![
-                "Refine search".to_string(),
-                "Show more details".to_string(),
-            ],
+            suggestions,
             related_queries: vec![
                 "Show similar results".to_string(),
             ],
             agents_used,
             execution_time_ms,
-            confidence: 0.85,
+            confidence: avg_confidence * real_fraction,
         })
     }
 
@@ -390,11 +564,47 @@ enum IntentType {
     Conversation,
 }
 
+impl IntentType {
+    /// Map the intent name stored in a `RoutingConfig` prefix table back to the enum.
+    fn from_config_str(name: &str) -> Self {
+        match name {
+            "search" => IntentType::Search,
+            "summarize" => IntentType::Summarize,
+            "analyze" => IntentType::Analyze,
+            "generate" => IntentType::Generate,
+            "execute" => IntentType::Execute,
+            _ => IntentType::Conversation,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
-struct AgentResult {
-    agent: AgentType,
-    data: serde_json::Value,
-    confidence: f32,
+pub(crate) struct AgentResult {
+    pub(crate) agent: String,
+    pub(crate) data: serde_json::Value,
+    pub(crate) confidence: f32,
+    /// `Some(reason)` when this result is a stand-in for an agent that errored or
+    /// timed out, rather than real agent output.
+    pub(crate) placeholder_reason: Option<String>,
+}
+
+impl AgentResult {
+    fn placeholder(agent_id: &str, reason: String) -> Self {
+        Self {
+            agent: agent_id.to_string(),
+            data: serde_json::json!({ "error": reason }),
+            confidence: 0.1,
+            placeholder_reason: Some(reason),
+        }
+    }
+}
+
+/// The registry id a built-in `AgentType` is registered under (its serde snake_case name).
+fn agent_type_id(agent_type: &AgentType) -> String {
+    serde_json::to_value(agent_type)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("{:?}", agent_type))
 }
 
 impl Default for ApolloRouter {
@@ -0,0 +1,238 @@
+// Apollo Routing Config Client
+//
+// The intent keyword prefixes and data-type -> agent mappings used to live compiled
+// into `router.rs`. They now live in a namespaced remote config center (ctrip-apollo
+// style: a namespace has a release id, and the client long-polls a notification
+// endpoint for that id to change before re-fetching). The active `RoutingConfig` is
+// kept behind an `ArcSwap` so `route_query` always reads the latest rules without a
+// restart, and the last good config is cached to disk so the router still boots if the
+// config service is unreachable.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use arc_swap::ArcSwap;
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+
+/// Compiled-down routing rules: intent keyword prefixes, data-type -> agent id
+/// mappings, per-agent enable flags, and per-agent time budgets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    pub release_id: String,
+    /// Query prefix (lowercased) -> intent type name, e.g. "show" -> "search".
+    pub intent_prefixes: HashMap<String, String>,
+    /// Data type -> agent id used by both the primary and supporting agent selectors.
+    pub data_type_agents: HashMap<String, String>,
+    /// Agent id -> whether `select_agents` may pick it at all.
+    pub agent_enabled: HashMap<String, bool>,
+    /// Agent id -> estimated time budget in milliseconds, used to fill `AgentPlan`.
+    pub estimated_time_ms: HashMap<String, u64>,
+    pub default_estimated_time_ms: u64,
+    pub default_agent: String,
+}
+
+impl RoutingConfig {
+    /// The hardcoded rules the router used before this subsystem existed, used as the
+    /// bootstrap default when neither the config center nor the disk cache are
+    /// reachable.
+    pub fn builtin_defaults() -> Self {
+        let intent_prefixes = [
+            ("show", "search"),
+            ("find", "search"),
+            ("summarize", "summarize"),
+            ("analyze", "analyze"),
+            ("create", "generate"),
+            ("generate", "generate"),
+            ("execute", "execute"),
+            ("trade", "execute"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let data_type_agents = [
+            ("email", "email_agent"),
+            ("meeting", "calendar_agent"),
+            ("transaction", "ledger_agent"),
+            ("document", "document_parser"),
+            ("code", "code_editor"),
+            ("trade", "trading_agent"),
+            ("health", "health_agent"),
+            ("fitness", "fitness_agent"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        Self {
+            release_id: "builtin".to_string(),
+            intent_prefixes,
+            data_type_agents,
+            agent_enabled: HashMap::new(),
+            estimated_time_ms: HashMap::new(),
+            default_estimated_time_ms: 1000,
+            default_agent: "knowledge_agent".to_string(),
+        }
+    }
+
+    pub fn is_agent_enabled(&self, agent_id: &str) -> bool {
+        self.agent_enabled.get(agent_id).copied().unwrap_or(true)
+    }
+
+    pub fn estimated_time_for(&self, agent_id: &str) -> u64 {
+        self.estimated_time_ms.get(agent_id).copied().unwrap_or(self.default_estimated_time_ms)
+    }
+}
+
+#[derive(Deserialize)]
+struct NotificationResponse {
+    namespace: String,
+    notification_id: i64,
+}
+
+/// Long-polling client for a single namespace in the remote config center.
+pub struct RemoteConfigClient {
+    http: reqwest::Client,
+    config_center_url: String,
+    namespace: String,
+    cache_path: PathBuf,
+    config: Arc<ArcSwap<RoutingConfig>>,
+    notification_id: std::sync::atomic::AtomicI64,
+}
+
+impl RemoteConfigClient {
+    /// A client wired to `RoutingConfig::builtin_defaults()` with no config center
+    /// to poll -- `current()` always returns the compiled-in defaults, and
+    /// `run_long_poll` should never be spawned against it. Used by
+    /// [`crate::router::ApolloRouter::new`] and
+    /// [`crate::router::ApolloRouter::with_plugin_dir`], which don't need a remote
+    /// config center at all.
+    pub fn builtin() -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            config_center_url: String::new(),
+            namespace: String::new(),
+            cache_path: PathBuf::new(),
+            config: Arc::new(ArcSwap::from_pointee(RoutingConfig::builtin_defaults())),
+            notification_id: std::sync::atomic::AtomicI64::new(-1),
+        }
+    }
+
+    /// Fetch the namespace once (falling back to the on-disk cache, then to
+    /// `RoutingConfig::builtin_defaults()`) so the router can boot even if the config
+    /// service is unreachable.
+    pub async fn bootstrap(config_center_url: String, namespace: String, cache_path: PathBuf) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+
+        let initial = match Self::fetch_namespace(&http, &config_center_url, &namespace).await {
+            Ok(config) => {
+                if let Err(e) = Self::write_cache(&cache_path, &config) {
+                    tracing::warn!(error = %e, "failed to persist routing config cache");
+                }
+                config
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "config center unreachable at startup, falling back to cache");
+                Self::read_cache(&cache_path).unwrap_or_else(RoutingConfig::builtin_defaults)
+            }
+        };
+
+        Self {
+            http,
+            config_center_url,
+            namespace,
+            cache_path,
+            config: Arc::new(ArcSwap::from_pointee(initial)),
+            notification_id: std::sync::atomic::AtomicI64::new(-1),
+        }
+    }
+
+    pub fn current(&self) -> Arc<RoutingConfig> {
+        self.config.load_full()
+    }
+
+    /// Long-poll the config center for a changed notification id on this namespace,
+    /// re-fetching and atomically swapping in the new `RoutingConfig` whenever it does.
+    /// Runs forever; spawn it as a background task.
+    pub async fn run_long_poll(self: Arc<Self>) {
+        loop {
+            match self.poll_once().await {
+                Ok(true) => {
+                    tracing::info!(namespace = %self.namespace, "routing config updated");
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::warn!(error = %e, "routing config long-poll failed, retrying");
+                    sleep(Duration::from_secs(5)).await;
+                }
+            }
+        }
+    }
+
+    async fn poll_once(&self) -> Result<bool> {
+        let current_id = self.notification_id.load(std::sync::atomic::Ordering::SeqCst);
+        let url = format!(
+            "{}/notifications/v2?namespace={}&notification_id={}",
+            self.config_center_url, self.namespace, current_id
+        );
+
+        // Blocking long-poll: the config center holds the connection open until the
+        // namespace's release changes or a server-side timeout elapses.
+        let response = self
+            .http
+            .get(&url)
+            .timeout(Duration::from_secs(90))
+            .send()
+            .await
+            .context("long-poll request failed")?;
+
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(false);
+        }
+
+        let notification: NotificationResponse =
+            response.error_for_status()?.json().await.context("invalid notification response")?;
+
+        let config = Self::fetch_namespace(&self.http, &self.config_center_url, &self.namespace).await?;
+        self.notification_id.store(notification.notification_id, std::sync::atomic::Ordering::SeqCst);
+        if let Err(e) = Self::write_cache(&self.cache_path, &config) {
+            tracing::warn!(error = %e, "failed to persist routing config cache");
+        }
+        self.config.store(Arc::new(config));
+
+        Ok(true)
+    }
+
+    async fn fetch_namespace(
+        http: &reqwest::Client,
+        config_center_url: &str,
+        namespace: &str,
+    ) -> Result<RoutingConfig> {
+        let url = format!("{config_center_url}/configs/{namespace}");
+        let response = http.get(&url).send().await.context("namespace fetch failed")?;
+        let config: RoutingConfig =
+            response.error_for_status()?.json().await.context("invalid namespace config payload")?;
+        Ok(config)
+    }
+
+    fn read_cache(path: &PathBuf) -> Option<RoutingConfig> {
+        let bytes = std::fs::read(path).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    fn write_cache(path: &PathBuf, config: &RoutingConfig) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let bytes = serde_json::to_vec_pretty(config)?;
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+}